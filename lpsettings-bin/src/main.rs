@@ -9,6 +9,7 @@ extern crate pretty_env_logger;
 extern crate updater_lp;
 
 use lpsettings::interface;
+use std::process;
 
 // upstream for the repository, used as the source of the releases
 static UPDATER_URL : &str = "https://github.com/snsvrno/lpsettings-rs";
@@ -46,14 +47,15 @@ fn main() {
     check_for_updates();
 
     // checks if the user wants to update
-    if let Some(_) = app.subcommand_matches("update") {
+    if app.subcommand_matches("update").is_some() {
         update_app();
     }
 
-    // processess the arguement matches.
-    match interface::process(&app) {
-        Err(error) => { error!("{}",error); }
-        Ok(_) => { }
+    // processess the arguement matches, exiting with a code
+    // based on what went wrong so scripts can check it.
+    if let Err(error) = interface::process(&app) {
+        interface::display_error(&error);
+        process::exit(lpsettings::error::exit_code(&error));
     }
 }
 
@@ -0,0 +1,121 @@
+//! snapshots of the settings files.
+//!
+//! a backup is a copy of the global or local settings file in the
+//! `backups` folder inside the global settings folder, named with
+//! the scope and the time it was made,
+//! `global-20190101T120000-000000000.toml`, so they sort oldest to
+//! newest and two backups never have the same name. the local
+//! backups are kept apart for each project, in
+//! `backups/projects/<project folder>`, so only the current project's
+//! backups are listed and compared against. the secrets file is never
+//! backed up.
+
+use std::fs::{ self, File, OpenOptions };
+use std::io;
+use std::path::{ Path, PathBuf };
+
+use failure::Error;
+use chrono;
+
+use error::ErrorKind;
+use project;
+use { get_folder, get_file_global, get_file_local };
+
+pub fn get_backup_folder() -> PathBuf {
+    let mut path = get_folder();
+    path.push("backups");
+    path
+}
+
+pub fn get_project_folder() -> PathBuf {
+    //! the folder the current project's local backups are kept in,
+    //! named after the project's folder
+
+    let name : String = project::get_folder().display().to_string().chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+
+    let mut path = get_backup_folder();
+    path.push("projects");
+    path.push(name.trim_start_matches('_'));
+    path
+}
+
+pub fn create(local : bool) -> Result<PathBuf,Error> {
+    //! copies the current settings file into the backups folder,
+    //! returns the path of the backup.
+
+    let source = if local { get_file_local(false) } else { get_file_global(false) };
+    if !Path::new(&source).exists() {
+        return Err(ErrorKind::InvalidInput(format!("There are no settings to back up, {} doesn't exist",source)).into());
+    }
+
+    let folder = if local { get_project_folder() } else { get_backup_folder() };
+    fs::create_dir_all(&folder)?;
+
+    let mut path = folder;
+    path.push(format!("{}-{}.toml",
+        if local { "local" } else { "global" },
+        chrono::Utc::now().format("%Y%m%dT%H%M%S-%9f")
+    ));
+
+    // never replaces another backup
+    let mut backup = OpenOptions::new().write(true).create_new(true).open(&path)
+        .map_err(|error| Error::from(error).context(format!("Cannot create the backup {}",path.display())))?;
+    io::copy(&mut File::open(&source)?,&mut backup)?;
+    info!("Backed up {} to {}",source,path.display());
+    Ok(path)
+}
+
+pub fn list() -> Result<Vec<PathBuf>,Error> {
+    //! the global backups and the current project's local backups,
+    //! oldest first
+
+    let mut backups : Vec<PathBuf> = Vec::new();
+    for folder in [get_backup_folder(), get_project_folder()].iter() {
+        if !folder.exists() { continue; }
+
+        for entry in fs::read_dir(folder)? {
+            let path = entry?.path();
+            if path.extension().map(|ext| ext == "toml").unwrap_or(false) {
+                backups.push(path);
+            }
+        }
+    }
+
+    // local backups from before they were kept per project could be
+    // from any project
+    backups.retain(|path| path.parent() != Some(get_backup_folder().as_path()) || !name(path).starts_with("local-"));
+
+    // by the time they were made, whatever the scope
+    backups.sort_by_key(|path| {
+        let name = name(path);
+        match name.find('-') {
+            Some(split) => name[split+1..].to_string(),
+            None => name,
+        }
+    });
+    Ok(backups)
+}
+
+fn name(path : &Path) -> String {
+    path.file_name().map(|file| file.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+pub fn find(name : &str) -> Result<PathBuf,Error> {
+    //! finds a backup by its file name, with or without the
+    //! extension, or `latest` for the newest one.
+
+    let backups = list()?;
+
+    let found = if name == "latest" {
+        backups.last().cloned()
+    } else {
+        backups.into_iter().find(|path| {
+            path.file_name().map(|file| file == name).unwrap_or(false)
+            || path.file_stem().map(|stem| stem == name).unwrap_or(false)
+        })
+    };
+
+    found.ok_or_else(|| ErrorKind::InvalidInput(format!("Backup '{}' doesn't exist",name)).into())
+}
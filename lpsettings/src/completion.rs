@@ -0,0 +1,186 @@
+//! shell completion scripts.
+//!
+//! the static part of the script (subcommands, flags) is generated
+//! by clap from the app, and a small part is added for each shell
+//! that asks the binary itself, `lpsettings complete keys` and
+//! `lpsettings complete values KEY`, for the keys and values.
+
+use std::io::Write;
+
+use clap;
+use failure::Error;
+
+use error::ErrorKind;
+use map;
+use scope::Scope;
+use store::Store;
+
+/// the shells that scripts can be generated for
+pub const SHELLS : [&str; 3] = [ "bash", "zsh", "fish" ];
+
+/// subcommands whose argument is a key
+const KEY_SUBCOMMANDS : [&str; 3] = [ "unset", "history", "list" ];
+
+/// options that take a value, which isn't a key or value to complete
+const VALUE_OPTIONS : [&str; 6] = [ "--format", "--profile", "--backup", "--color", "--from", "--set" ];
+
+pub fn keys() -> Result<Vec<String>,Error> {
+    //! every key that can be completed, the valid keys in the
+    //! options map and the keys that are in the settings files,
+    //! including the inherited and system ones.
+
+    let mut keys : Vec<String> = map::create_options_map()?.into_iter()
+        .filter(map::is_current)
+        .map(|m| m.key)
+        .collect();
+
+    keys.append(&mut Store::load()?.keys(&Scope::Best));
+
+    keys.sort();
+    keys.dedup();
+    Ok(keys)
+}
+
+pub fn values(key : &str) -> Result<Vec<String>,Error> {
+    //! the values that can be completed for the key, only keys with
+    //! `choices` in the options map have any.
+
+    let map = map::create_options_map()?;
+    Ok(map::get_choices(&map,key).to_vec())
+}
+
+pub fn generate<W : Write>(app : &mut clap::App, shell : &str, out : &mut W) -> Result<(),Error> {
+    //! writes the completion script for the shell.
+
+    let bin = app.get_name().to_string();
+    let parsed = shell.parse::<clap::Shell>()
+        .map_err(|_| ErrorKind::InvalidInput(format!("'{}' is not a supported shell, use one of {}",shell,SHELLS.join(", "))))?;
+
+    let mut script : Vec<u8> = Vec::new();
+    app.gen_completions_to(bin.to_string(),parsed,&mut script);
+    let script = String::from_utf8(script)?;
+
+    let script = match parsed {
+        clap::Shell::Bash => format!("{}\n{}",script,bash(&bin)),
+        clap::Shell::Zsh => zsh(&script,&bin),
+        clap::Shell::Fish => format!("{}\n{}",script,fish(&bin)),
+        _ => script,
+    };
+
+    out.write_all(script.as_bytes())?;
+    Ok(())
+}
+
+fn bash(bin : &str) -> String {
+    //! wraps the clap completion function, adding keys for the first
+    //! argument and values for the second.
+
+    format!(r#"_{bin}_dynamic() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local words=() word skip=0
+    for word in "${{COMP_WORDS[@]:1:COMP_CWORD-1}}"; do
+        if [ "$skip" = 1 ]; then skip=0; continue; fi
+        case "$word" in
+            {options}) skip=1 ;;
+            -*) ;;
+            *) words+=("$word") ;;
+        esac
+    done
+
+    _{bin} "$@"
+    [[ "$cur" == -* ]] && return
+
+    case "${{#words[@]}}:${{words[0]}}" in
+        0:*)
+            COMPREPLY+=($(compgen -W "$({bin} complete keys 2>/dev/null)" -- "$cur")) ;;
+        {subcommands})
+            COMPREPLY=($(compgen -W "$({bin} complete keys 2>/dev/null)" -- "$cur")) ;;
+        1:*)
+            local values="$({bin} complete values "${{words[0]}}" 2>/dev/null)"
+            [ -n "$values" ] && COMPREPLY=($(compgen -W "$values" -- "$cur")) ;;
+    esac
+}}
+complete -F _{bin}_dynamic -o bashdefault -o default {bin}
+"#, bin = bin, options = VALUE_OPTIONS.join("|"), subcommands = KEY_SUBCOMMANDS.iter()
+        .map(|subcommand| format!("1:{}",subcommand))
+        .collect::<Vec<String>>()
+        .join("|"))
+}
+
+fn zsh(script : &str, bin : &str) -> String {
+    //! points the KEY and VALUE arguments at functions that ask the
+    //! binary, instead of completing files.
+
+    let helpers = format!(r#"
+_{bin}_keys() {{
+    local -a keys
+    keys=(${{(f)"$({bin} complete keys 2>/dev/null)"}})
+    compadd -a keys
+}}
+
+_{bin}_values() {{
+    local -a values
+    values=(${{(f)"$({bin} complete values "${{words[CURRENT-1]}}" 2>/dev/null)"}})
+    compadd -a values
+}}
+"#, bin = bin);
+
+    let script : Vec<String> = script.lines()
+        .map(|line| {
+            if !line.ends_with(":_files' \\") { return line.to_string(); }
+            if line.contains(":KEY") || line.contains(":FILTER") {
+                line.replace(":_files'",&format!(":_{}_keys'",bin))
+            } else if line.contains(":VALUE") {
+                line.replace(":_files'",&format!(":_{}_values'",bin))
+            } else {
+                line.to_string()
+            }
+        }).collect();
+    let script = script.join("\n");
+
+    // the helpers need to be defined before the script calls itself
+    let call = format!("_{} \"$@\"",bin);
+    match script.rfind(&call) {
+        Some(index) => format!("{}{}\n{}",&script[..index],helpers,&script[index..]),
+        None => format!("{}{}",script,helpers),
+    }
+}
+
+fn fish(bin : &str) -> String {
+    //! completes keys for the first argument and values for the second
+
+    format!(r#"function __fish_{bin}_args
+    set -l words (commandline -opc)
+    set -e words[1]
+    set -l args
+    set -l skip 0
+    for word in $words
+        if test $skip = 1
+            set skip 0
+            continue
+        end
+        switch $word
+            case {options}
+                set skip 1
+            case '-*'
+            case '*'
+                set args $args $word
+        end
+    end
+    printf '%s\n' $args
+end
+
+function __fish_{bin}_arg_count
+    test (count (__fish_{bin}_args)) = $argv[1]
+end
+
+function __fish_{bin}_key_subcommand
+    set -l args (__fish_{bin}_args)
+    test (count $args) = 1; and contains -- $args[1] {subcommands}
+end
+
+complete -c {bin} -n '__fish_{bin}_arg_count 0' -f -a '({bin} complete keys 2>/dev/null)'
+complete -c {bin} -n '__fish_{bin}_key_subcommand' -f -a '({bin} complete keys 2>/dev/null)'
+complete -c {bin} -n '__fish_{bin}_arg_count 1; and not __fish_{bin}_key_subcommand' -f -a '({bin} complete values (__fish_{bin}_args)[1] 2>/dev/null)'
+"#, bin = bin, options = VALUE_OPTIONS.join(" "), subcommands = KEY_SUBCOMMANDS.join(" "))
+}
@@ -0,0 +1,132 @@
+//! encrypting individual values at rest.
+//!
+//! encrypted values are stored in the settings file as text,
+//! `enc:v1:` followed by the base64 nonce and ciphertext. the key
+//! lives in a separate local key file outside of `~/.lovepack`, so
+//! the settings folder can be synced (dotfile repos) without the
+//! values being readable.
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::collections::HashMap;
+
+use failure::Error;
+use base64;
+use dirs;
+use serde_json;
+use chacha20poly1305::{ ChaCha20Poly1305, Key, Nonce };
+use chacha20poly1305::aead::{ Aead, AeadCore, KeyInit, OsRng };
+
+use secrets;
+use Type;
+
+/// the environmental variable that moves the key file
+pub const ENV_VAR : &str = "LOVEPACK_KEY_FILE";
+
+/// marks a text value as encrypted
+const PREFIX : &str = "enc:v1:";
+const NONCE_LENGTH : usize = 12;
+const KEY_LENGTH : usize = 32;
+
+pub fn get_key_path() -> PathBuf {
+    //! the location of the key file, can be overridden with the
+    //! `LOVEPACK_KEY_FILE` environmental variable.
+
+    if let Ok(path) = env::var(ENV_VAR) {
+        return PathBuf::from(path);
+    }
+
+    let mut path = dirs::data_local_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_default();
+    path.push("lovepack");
+    path.push("lovepack.key");
+    path
+}
+
+pub fn is_encrypted(value : &Type) -> bool {
+    match value {
+        Type::Text(text) => text.starts_with(PREFIX),
+        _ => false,
+    }
+}
+
+pub fn encrypt(value : &Type) -> Result<Type,Error> {
+    //! encrypts the value, creating the key file if this is the
+    //! first time something is encrypted.
+
+    let cipher = ChaCha20Poly1305::new(&load_key(true)?);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let plain = serde_json::to_string(value)?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.append(&mut cipher.encrypt(&nonce,plain.as_bytes())
+        .map_err(|_| format_err!("Failed to encrypt value"))?);
+
+    Ok(Type::Text(format!("{}{}",PREFIX,base64::encode(&sealed))))
+}
+
+pub fn decrypt(value : Type) -> Result<Type,Error> {
+    //! decrypts the value if it is encrypted, including any
+    //! encrypted values inside of tables and arrays.
+
+    match value {
+        Type::Text(ref text) if text.starts_with(PREFIX) => {
+            let sealed = base64::decode(&text[PREFIX.len()..])?;
+            if sealed.len() < NONCE_LENGTH {
+                return Err(format_err!("Encrypted value is too short"));
+            }
+
+            let cipher = ChaCha20Poly1305::new(&load_key(false)?);
+            let nonce = Nonce::from_slice(&sealed[..NONCE_LENGTH]);
+            let plain = cipher.decrypt(nonce,&sealed[NONCE_LENGTH..])
+                .map_err(|_| format_err!("Failed to decrypt value, was it encrypted with a different key file?"))?;
+
+            Ok(serde_json::from_slice(&plain)?)
+        },
+        Type::Complex(hash) => {
+            let mut decrypted = HashMap::new();
+            for (key,value) in hash {
+                decrypted.insert(key,decrypt(value)?);
+            }
+            Ok(Type::Complex(decrypted))
+        },
+        Type::Array(array) => {
+            let mut decrypted = Vec::new();
+            for value in array {
+                decrypted.push(decrypt(value)?);
+            }
+            Ok(Type::Array(decrypted))
+        },
+        value => Ok(value),
+    }
+}
+
+fn load_key(create : bool) -> Result<Key,Error> {
+    let path = get_key_path();
+
+    match File::open(&path) {
+        Ok(mut file) => {
+            let mut bytes : Vec<u8> = Vec::new();
+            file.read_to_end(&mut bytes)?;
+            if bytes.len() != KEY_LENGTH {
+                return Err(format_err!("Key file {} is not a valid key",path.display()));
+            }
+            Ok(*Key::from_slice(&bytes))
+        },
+        Err(ref error) if create && error.kind() == io::ErrorKind::NotFound => {
+            info!("Creating key file {}",path.display());
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+            let path = path.display().to_string();
+            secrets::open_restricted(&path)?.write_all(&key)?;
+            Ok(key)
+        },
+        Err(error) => Err(format_err!("Cannot read key file {}: {}",path.display(),error)),
+    }
+}
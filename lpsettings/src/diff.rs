@@ -0,0 +1,201 @@
+//! comparing two sets of settings, the local and global scopes,
+//! the current settings and another file, or a backup and now.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::collections::BTreeMap;
+
+use failure::Error;
+use serde_json;
+use settingsfile::Settings;
+
+use settings::Configuration;
+use error::ErrorKind;
+use output::OutputFormat;
+use scope::Scope;
+use store::Store;
+use backup;
+use theme;
+use secrets;
+use crypt;
+use Type;
+
+/// every key, flattened, with its value
+pub type Values = BTreeMap<String,Type>;
+
+#[derive(Serialize,Clone,Copy,PartialEq,Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// a key that is different between the two sets of settings
+#[derive(Serialize)]
+pub struct Change {
+    pub key : String,
+    pub kind : Kind,
+    pub old : Option<Type>,
+    pub new : Option<Type>,
+}
+
+pub fn compare(old : &Values, new : &Values) -> Vec<Change> {
+    //! the keys that are added, removed or changed going from `old`
+    //! to `new`, sorted by key.
+    //!
+    //! encrypted values are compared as they are stored.
+
+    let mut changes : Vec<Change> = Vec::new();
+
+    for (key,old_value) in old.iter() {
+        match new.get(key) {
+            None => changes.push(Change {
+                key : key.to_string(), kind : Kind::Removed,
+                old : Some(old_value.clone()), new : None,
+            }),
+            Some(new_value) if new_value != old_value => changes.push(Change {
+                key : key.to_string(), kind : Kind::Changed,
+                old : Some(old_value.clone()), new : Some(new_value.clone()),
+            }),
+            Some(_) => { },
+        }
+    }
+
+    for (key,new_value) in new.iter() {
+        if !old.contains_key(key) {
+            changes.push(Change {
+                key : key.to_string(), kind : Kind::Added,
+                old : None, new : Some(new_value.clone()),
+            });
+        }
+    }
+
+    changes.sort_by(|a,b| a.key.cmp(&b.key));
+    changes
+}
+
+pub fn load_scope(location : &Scope) -> Result<Values,Error> {
+    //! all the values of the scope, including the secrets. `Best`
+    //! is the local settings, with the projects they inherit from, on
+    //! top of the global ones, on top of the system ones.
+
+    let store = Store::load()?;
+    let mut values = Values::new();
+    for key in store.keys(location) {
+        if let Some(value) = store.get_stored(&key,location) { values.insert(key,value); }
+    }
+
+    Ok(values)
+}
+
+pub fn load_file<P : AsRef<Path>>(path : P) -> Result<Values,Error> {
+    //! all the values in the settings file
+
+    let file = File::open(path.as_ref())
+        .map_err(|error| Error::from(error).context(format!("Cannot open {}",path.as_ref().display())))?;
+    let settings = Settings::create_from(&file,Configuration::new())?;
+
+    let mut values = Values::new();
+    for key in settings.keys() {
+        if let Some(value) = settings.get_value(&key) { values.insert(key,value); }
+    }
+
+    Ok(values)
+}
+
+pub fn scopes() -> Result<Vec<Change>,Error> {
+    //! what the local settings change, going from the global (and
+    //! system) settings to the merged settings that are used. keys
+    //! only set globally are the same in both, so they aren't listed.
+
+    let mut old = load_scope(&Scope::System)?;
+    old.extend(load_scope(&Scope::Global)?);
+
+    Ok(compare(&old,&load_scope(&Scope::Best)?))
+}
+
+pub fn file<P : AsRef<Path>>(path : P, location : &Scope) -> Result<Vec<Change>,Error> {
+    //! what would change going from the current settings to the file
+
+    Ok(compare(&load_scope(location)?,&load_file(path)?))
+}
+
+pub fn backup(name : &str) -> Result<Vec<Change>,Error> {
+    //! what changed since the backup, compared against the scope
+    //! the backup was made of.
+
+    let path = backup::find(name)?;
+    let local = path.file_name()
+        .map(|file| file.to_string_lossy().starts_with("local-"))
+        .unwrap_or(false);
+    let location = if local { Scope::Local } else { Scope::Global };
+
+    let mut old = load_file(&path)?;
+    let mut new = load_scope(&location)?;
+    // backups don't have the secrets, so they aren't compared
+    old.retain(|key,_| !secrets::is_secret(key));
+    new.retain(|key,_| !secrets::is_secret(key));
+
+    Ok(compare(&old,&new))
+}
+
+pub fn write<W : Write>(out : &mut W, format : OutputFormat, changes : &[Change]) -> Result<(),Error> {
+    //! writes the changes, secret values are masked.
+
+    let changes : Vec<Change> = changes.iter()
+        .map(|change| Change {
+            key : change.key.to_string(),
+            kind : change.kind,
+            old : change.old.clone().map(|value| secrets::mask(&change.key,value)),
+            new : change.new.clone().map(|value| secrets::mask(&change.key,value)),
+        }).collect();
+
+    match format {
+        OutputFormat::Json => writeln!(out,"{}",serde_json::to_string_pretty(&changes)?)?,
+        OutputFormat::Toml => return Err(ErrorKind::InvalidInput("diff can't be written as toml, use json".to_string()).into()),
+        OutputFormat::Raw => {
+            for change in changes.iter() {
+                match change.kind {
+                    Kind::Added => writeln!(out,"+ {}={}",change.key,display(&change.new))?,
+                    Kind::Removed => writeln!(out,"- {}={}",change.key,display(&change.old))?,
+                    Kind::Changed => writeln!(out,"~ {}={} -> {}",change.key,display(&change.old),display(&change.new))?,
+                }
+            }
+        },
+        OutputFormat::Text => {
+            if changes.is_empty() {
+                writeln!(out,"No differences")?;
+            }
+
+            for change in changes.iter() {
+                match change.kind {
+                    Kind::Added => writeln!(out,"{} {}: {}",
+                        theme::key_value_set("+"),
+                        theme::key(&change.key),
+                        theme::key_value_set(display(&change.new)))?,
+                    Kind::Removed => writeln!(out,"{} {}: {}",
+                        theme::error("-"),
+                        theme::key(&change.key),
+                        theme::error(display(&change.old)))?,
+                    Kind::Changed => writeln!(out,"{} {}: {} -> {}",
+                        theme::key("~"),
+                        theme::key(&change.key),
+                        theme::key_value(display(&change.old)),
+                        theme::key_value_set(display(&change.new)))?,
+                }
+            }
+        },
+    }
+
+    Ok(())
+}
+
+fn display(value : &Option<Type>) -> String {
+    match value {
+        Some(ref value) if crypt::is_encrypted(value) => "[encrypted]".to_string(),
+        Some(value) => format!("{}",value),
+        None => String::new(),
+    }
+}
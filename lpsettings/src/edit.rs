@@ -0,0 +1,258 @@
+//! editing a settings file in the user's editor.
+//!
+//! the file is copied to a temporary file which is opened in
+//! `$VISUAL` or `$EDITOR`. when the editor is closed the values that
+//! were changed are checked, and if they aren't valid the editor is
+//! opened again with the errors at the top of the file. the real file
+//! is only replaced once the copy is valid, in one rename.
+
+use std::env;
+use std::fs::{ self, File, OpenOptions };
+use std::io::{ self, Read, Write };
+use std::path::{ Path, PathBuf };
+use std::process::{ self, Command };
+
+use failure::Error;
+use toml;
+
+use error::ErrorKind;
+use map;
+use diff;
+use value;
+use secrets;
+use history;
+use scope::Scope;
+use system;
+use { get_file_global, get_file_local };
+
+/// lines starting with this are the errors from the last attempt,
+/// and are removed before the file is checked
+const ERROR_PREFIX : &str = "# lpsettings error: ";
+
+pub fn run(scope : Scope) -> Result<Vec<String>,Error> {
+    //! edits the global, local or system settings file, returns the
+    //! keys that were changed.
+
+    let (path, scope) = match scope {
+        Scope::Local => (get_file_local(false),"local"),
+        Scope::System => (system::get_file().display().to_string(),"system"),
+        _ => (get_file_global(false),"global"),
+    };
+    let original = read(&path)?;
+
+    let (temp, mut file) = create_temp()?;
+    file.write_all(original.as_bytes())?;
+    drop(file);
+    let result = edit_until_valid(&temp,&original);
+    fs::remove_file(&temp).ok();
+    let edited = result?;
+
+    if edited == original { return Ok(Vec::new()); }
+
+    // the changes for the history, done before writing in case
+    // the old file can't be read as settings.
+    let old = parse(&original).unwrap_or_default();
+    let new = parse(&edited)?;
+    let changes : Vec<history::Change> = diff::compare(&old,&new).into_iter()
+        .map(|change| history::Change::new("edit",scope,&path,&change.key,change.old,change.new))
+        .collect();
+
+    if let Some(parent) = Path::new(&path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    replace(Path::new(&path),&edited)?;
+    history::record(&changes);
+
+    Ok(changes.into_iter().map(|change| change.key).collect())
+}
+
+fn edit_until_valid(temp : &Path, original : &str) -> Result<String,Error> {
+    //! opens the editor until the file is valid, or the user closes
+    //! it without changing anything after being shown the errors.
+
+    // the text the errors were last shown for
+    let mut shown : Option<String> = None;
+
+    loop {
+        open_editor(temp)?;
+        let edited = strip_errors(&read(temp)?);
+
+        let errors = check(original,&edited);
+        if errors.is_empty() { return Ok(edited); }
+
+        if shown.as_ref() == Some(&edited) {
+            return Err(ErrorKind::InvalidInput(format!("The file still isn't valid, nothing was saved: {}",errors.join(", "))).into());
+        }
+        shown = Some(edited.to_string());
+
+        let mut annotated : String = errors.iter()
+            .map(|error| format!("{}{}\n",ERROR_PREFIX,error.replace('\n'," ")))
+            .collect();
+        annotated.push_str(&edited);
+        write(temp,&annotated)?;
+    }
+}
+
+fn check(original : &str, text : &str) -> Vec<String> {
+    //! everything that is wrong with the values that were changed,
+    //! what was already in the file is left as it is
+
+    let old = parse(original).unwrap_or_default();
+    let values = match parse(text) {
+        Ok(values) => values,
+        Err(error) => return vec![error.to_string()],
+    };
+
+    let map = match map::create_options_map() {
+        Ok(map) => map,
+        Err(error) => return vec![error.to_string()],
+    };
+
+    let mut errors : Vec<String> = Vec::new();
+    for (key,value) in values.iter().filter(|(key,value)| old.get(*key) != Some(*value)) {
+        if secrets::is_secret(key) {
+            errors.push(format!("'{}' is secret and is kept in the secrets file, set it with `lpsettings {} VALUE`",key,key));
+        }
+        if let Err(error) = value::check(&map,key,value) {
+            errors.push(error.to_string());
+        }
+    }
+
+    errors
+}
+
+fn parse(text : &str) -> Result<diff::Values,Error> {
+    //! the flattened values in the text
+
+    let mut values = diff::Values::new();
+    if text.trim().is_empty() { return Ok(values); }
+
+    let settings : toml::Value = toml::de::from_str(text)
+        .map_err(|error| ErrorKind::Parse(error.to_string()))?;
+    flatten(&settings,"",&mut values)?;
+    Ok(values)
+}
+
+fn flatten(value : &toml::Value, prefix : &str, values : &mut diff::Values) -> Result<(),Error> {
+    match value {
+        toml::Value::Table(table) => {
+            for (key,value) in table {
+                let key = if prefix.is_empty() { key.to_string() } else { format!("{}.{}",prefix,key) };
+                flatten(value,&key,values)?;
+            }
+        },
+        value => {
+            let value = value.clone().try_into()
+                .map_err(|_| ErrorKind::Parse(format!("'{}' isn't a value that can be used in the settings",prefix)))?;
+            values.insert(prefix.to_string(),value);
+        },
+    }
+    Ok(())
+}
+
+fn strip_errors(text : &str) -> String {
+    if !text.contains(ERROR_PREFIX) { return text.to_string(); }
+
+    text.lines()
+        .filter(|line| !line.starts_with(ERROR_PREFIX))
+        .map(|line| format!("{}\n",line))
+        .collect()
+}
+
+fn open_editor(path : &Path) -> Result<(),Error> {
+    //! runs the editor on the file and waits for it to close
+
+    let editor = env::var("VISUAL").ok().filter(|editor| !editor.is_empty())
+        .or_else(|| env::var("EDITOR").ok().filter(|editor| !editor.is_empty()))
+        .unwrap_or_else(|| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() });
+
+    // the editor can have arguments, `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|error| Error::from(error).context(format!("Cannot start editor '{}'",editor)))?;
+
+    if !status.success() {
+        return Err(format_err!("Editor '{}' exited with {}, nothing was saved",editor,status));
+    }
+
+    Ok(())
+}
+
+fn create_temp() -> Result<(PathBuf,File),Error> {
+    //! a new temporary file for the copy that is edited
+
+    create_temp_in(&env::temp_dir(),"lpsettings-edit")
+}
+
+fn create_temp_in(folder : &Path, name : &str) -> Result<(PathBuf,File),Error> {
+    //! a new temporary file in the folder that only the user can read.
+    //! a file that is already there, or a link, is never written
+    //! through, the next name is tried instead.
+
+    for attempt in 0..100 {
+        let path = folder.join(format!("{}-{}-{}.toml",name,process::id(),attempt));
+        match create_new(&path) {
+            Ok(file) => return Ok((path,file)),
+            Err(ref error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(Error::from(error).context(format!("Cannot create {}",path.display())).into()),
+        }
+    }
+
+    Err(format_err!("Cannot create a temporary file in {}",folder.display()))
+}
+
+fn replace(path : &Path, text : &str) -> Result<(),Error> {
+    //! replaces the settings file with the text. it is written next to
+    //! the file first and then renamed over it, so the file is never
+    //! left half written. the file keeps its permissions.
+
+    let folder = path.parent().unwrap_or_else(|| Path::new("."));
+    let (temp, mut file) = create_temp_in(folder,".lpsettings-save")?;
+
+    let result = file.write_all(text.as_bytes())
+        .and_then(|_| file.sync_all())
+        .and_then(|_| match fs::metadata(path) {
+            Ok(metadata) => fs::set_permissions(&temp,metadata.permissions()),
+            Err(_) => Ok(()),
+        })
+        .and_then(|_| fs::rename(&temp,path));
+
+    if let Err(error) = result {
+        fs::remove_file(&temp).ok();
+        return Err(Error::from(error).context(format!("Cannot save {}",path.display())).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn create_new(path : &Path) -> io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    OpenOptions::new().write(true).create_new(true).mode(0o600).open(path)
+}
+
+#[cfg(not(unix))]
+fn create_new(path : &Path) -> io::Result<File> {
+    OpenOptions::new().write(true).create_new(true).open(path)
+}
+
+fn read<P : AsRef<Path>>(path : P) -> Result<String,Error> {
+    //! the contents of the file, empty if it doesn't exist
+
+    let mut text = String::new();
+    if let Ok(mut file) = File::open(path.as_ref()) {
+        file.read_to_string(&mut text)?;
+    }
+    Ok(text)
+}
+
+fn write<P : AsRef<Path>>(path : P, text : &str) -> Result<(),Error> {
+    File::create(path.as_ref())?.write_all(text.as_bytes())?;
+    Ok(())
+}
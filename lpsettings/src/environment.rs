@@ -0,0 +1,70 @@
+//! overriding settings with environmental variables.
+//!
+//! any key can be set for a single run with `LOVEPACK_` and the
+//! key in capitals, using `__` between the parts of the key, so
+//! `user.name` is `LOVEPACK_USER__NAME`. these override every other
+//! place the key is set, for every read through `Scope::Best`, so
+//! `get_value` in any app using the library sees them too, not only
+//! the command line. reads of a single scope (`Local`, `Global`,
+//! `System`) ignore them.
+//!
+//! the variables that configure lovepack itself, like
+//! `LOVEPACK_SYSTEM_DIR`, are never read as keys.
+//!
+//! the value is read as the key's type in the options map, and as
+//! text for keys without one or values that aren't of that type.
+
+use std::env;
+
+use map;
+use value;
+use scope;
+use system;
+use profile;
+use crypt;
+use Type;
+
+const PREFIX : &str = "LOVEPACK_";
+const SEPARATOR : &str = "__";
+
+/// the variables with the prefix that aren't keys
+const RESERVED : [&str; 4] = [ scope::ENV_VAR, system::ENV_VAR, profile::ENV_VAR, crypt::ENV_VAR ];
+
+pub fn get_name(key : &str) -> String {
+    //! the environmental variable that overrides the key
+
+    format!("{}{}",PREFIX,key.replace('.',SEPARATOR).to_uppercase())
+}
+
+pub fn get_value(key : &str) -> Option<Type> {
+    //! the value of the key from the environment, if it is set
+
+    let name = get_name(key);
+    if RESERVED.contains(&name.as_str()) { return None; }
+
+    match env::var(name) {
+        Ok(ref value) if !value.is_empty() => Some(parse(key,value)),
+        _ => None,
+    }
+}
+
+pub fn list() -> Vec<(String,Type)> {
+    //! all the keys that are set in the environment, with their values
+
+    let mut keys : Vec<(String,Type)> = env::vars()
+        .filter(|(name,value)| name.starts_with(PREFIX) && name.len() > PREFIX.len() && !value.is_empty())
+        .filter(|(name,_)| !RESERVED.contains(&name.as_str()))
+        .map(|(name,value)| {
+            let key = name[PREFIX.len()..].to_lowercase().replace(SEPARATOR,".");
+            let value = parse(&key,&value);
+            (key,value)
+        }).collect();
+
+    keys.sort_by(|a,b| a.0.cmp(&b.0));
+    keys
+}
+
+fn parse(key : &str, value : &str) -> Type {
+    value::parse_for(map::get_options_map(),key,None,value)
+        .unwrap_or_else(|_| Type::Text(value.to_string()))
+}
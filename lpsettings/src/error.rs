@@ -0,0 +1,73 @@
+//! the kinds of errors that can happen, so an application (like the
+//! binary) can tell them apart and exit with a meaningful code.
+//!
+//! errors are still passed around as `failure::Error`, use
+//! [`exit_code`] to find out what kind an error is.
+
+use std::io;
+use std::num;
+use std::fmt;
+
+use failure::{ Error, Fail };
+use toml;
+use serde_json;
+use base64;
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    /// the setting isn't defined
+    KeyNotFound(String),
+    /// something the user gave us can't be used
+    InvalidInput(String),
+    /// a file couldn't be parsed
+    Parse(String),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::KeyNotFound(key) => write!(f,"'{}' is not defined",key),
+            ErrorKind::InvalidInput(message) => write!(f,"{}",message),
+            ErrorKind::Parse(message) => write!(f,"{}",message),
+        }
+    }
+}
+
+impl Fail for ErrorKind { }
+
+/// exit codes used by the binary for each kind of error
+pub mod code {
+    pub const OK : i32 = 0;
+    /// anything that isn't one of the other kinds
+    pub const GENERAL : i32 = 1;
+    pub const KEY_NOT_FOUND : i32 = 2;
+    pub const INVALID_INPUT : i32 = 3;
+    pub const IO : i32 = 4;
+    pub const PARSE : i32 = 5;
+}
+
+pub fn exit_code(error : &Error) -> i32 {
+    //! the exit code for the error, checks the error and everything
+    //! that caused it and uses the first one that has a known kind.
+
+    for cause in error.iter_chain() {
+        if let Some(kind) = cause.downcast_ref::<ErrorKind>() {
+            return match kind {
+                ErrorKind::KeyNotFound(_) => code::KEY_NOT_FOUND,
+                ErrorKind::InvalidInput(_) => code::INVALID_INPUT,
+                ErrorKind::Parse(_) => code::PARSE,
+            };
+        }
+
+        if cause.downcast_ref::<io::Error>().is_some() { return code::IO; }
+
+        if cause.downcast_ref::<toml::de::Error>().is_some()
+        || cause.downcast_ref::<serde_json::Error>().is_some()
+        || cause.downcast_ref::<base64::DecodeError>().is_some()
+        || cause.downcast_ref::<num::ParseIntError>().is_some() {
+            return code::PARSE;
+        }
+    }
+
+    code::GENERAL
+}
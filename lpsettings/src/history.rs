@@ -0,0 +1,229 @@
+//! a journal of every change made through the library.
+//!
+//! each mutation (set, unset, init, import) is appended as a
+//! single json line to `history.jsonl` in the global settings
+//! folder, so the changes can be reviewed and reverted later. the
+//! changes a command makes together share a `group`, so undoing it
+//! reverts all of them.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::env;
+use std::process;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+
+use failure::Error;
+use chrono;
+use serde_json;
+use settingsfile::Settings;
+
+use settings::Configuration;
+use secrets;
+use get_folder;
+use Type;
+
+/// a single recorded change
+#[derive(Serialize,Deserialize,Clone)]
+pub struct Change {
+    /// when the change was made, rfc3339
+    pub timestamp : String,
+    /// the app that made the change
+    pub app : String,
+    /// what kind of change it was (set, unset, init, import)
+    pub action : String,
+    /// the scope that was changed, `global`, `local` or `system`
+    pub scope : String,
+    /// the settings file that was changed
+    pub file : String,
+    pub key : String,
+    /// the key is secret, so the values were masked and can't be reverted
+    #[serde(default)]
+    pub secret : bool,
+    pub old : Option<Type>,
+    pub new : Option<Type>,
+    /// the command that made the change, shared by every change it
+    /// made. empty for entries written before changes were grouped.
+    #[serde(default)]
+    pub group : String,
+}
+
+impl Change {
+    pub fn new(action : &str, scope : &str, file : &str, key : &str, old : Option<Type>, new : Option<Type>) -> Change {
+        //! creates a change stamped with the current time, secret 
+        //! values are never written to the journal.
+
+        let secret = secrets::is_secret(key);
+        let old = old.map(|value| secrets::mask(key,value));
+        let new = new.map(|value| secrets::mask(key,value));
+
+        Change {
+            timestamp : chrono::Utc::now().to_rfc3339(),
+            app : app_name(),
+            action : action.to_string(),
+            scope : scope.to_string(),
+            file : file.to_string(),
+            key : key.to_string(),
+            secret,
+            old,
+            new,
+            group : String::new(),
+        }
+    }
+}
+
+/// keeps the groups of one process apart when the clock is coarse
+static GROUPS : AtomicUsize = AtomicUsize::new(0);
+
+pub fn get_path() -> PathBuf {
+    //! the location of the journal file
+
+    let mut path = get_folder();
+    path.push("history.jsonl");
+    path
+}
+
+pub(crate) fn record(changes : &[Change]) {
+    //! appends the changes to the journal as one group, they are
+    //! undone together.
+    //!
+    //! failing to write the journal shouldn't fail the change that
+    //! was already saved, so errors are only logged.
+
+    if let Err(error) = append(changes) {
+        warn!("Could not record change history: {}",error);
+    }
+}
+
+fn append(changes : &[Change]) -> Result<(),Error> {
+    let group = format!("{}-{}-{}",
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0),
+        process::id(),
+        GROUPS.fetch_add(1,Ordering::SeqCst));
+
+    fs::create_dir_all(get_folder())?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(get_path())?;
+
+    for change in changes {
+        let mut change = change.clone();
+        change.group = group.clone();
+        writeln!(file,"{}",serde_json::to_string(&change)?)?;
+    }
+
+    Ok(())
+}
+
+pub fn get_changes(key : Option<&str>) -> Result<Vec<Change>,Error> {
+    //! reads the journal, oldest change first.
+    //!
+    //! if a `key` is given then only changes to that key (or keys
+    //! below it) are returned.
+
+    let changes = load()?;
+
+    match key {
+        None => Ok(changes),
+        Some(key) => {
+            let prefix = format!("{}.",key);
+            Ok(changes.into_iter()
+                .filter(|c| c.key == key || c.key.starts_with(&prefix))
+                .collect())
+        }
+    }
+}
+
+pub fn undo(count : usize) -> Result<Vec<Change>,Error> {
+    //! reverts the last `count` commands, newest change first, and
+    //! removes them from the journal. all the changes a command made
+    //! are reverted together, like every key of an import.
+    //!
+    //! returns the changes that were reverted.
+
+    let mut changes = load()?;
+    let mut undone : Vec<Change> = Vec::new();
+
+    for _ in 0..count {
+        let group = match changes.last() {
+            None => break,
+            Some(change) => change.group.clone(),
+        };
+
+        while let Some(change) = changes.pop() {
+            if let Err(error) = revert(&change) {
+                // keeps the journal in sync with what was actually reverted
+                changes.push(change);
+                save(&changes)?;
+                return Err(error);
+            }
+            undone.push(change);
+
+            // ungrouped entries are each their own command
+            let same_group = changes.last().map(|next| !group.is_empty() && next.group == group);
+            if same_group != Some(true) { break; }
+        }
+    }
+
+    save(&changes)?;
+    Ok(undone)
+}
+
+fn revert(change : &Change) -> Result<(),Error> {
+    //! puts the old value back into the file that was changed
+
+    if change.secret {
+        warn!("{} is secret, its value wasn't recorded so it can't be reverted",change.key);
+        return Ok(());
+    }
+
+    let mut settings = match File::open(&change.file) {
+        Ok(file) => Settings::create_from(&file,Configuration::new())?,
+        Err(_) => Settings::new(Configuration::new()),
+    };
+
+    match change.old {
+        Some(ref old) => settings.set_value(&change.key,old)?,
+        None => { settings.delete_key(&change.key); },
+    }
+
+    let file = File::create(&change.file)?;
+    settings.save_to(&file)
+}
+
+fn load() -> Result<Vec<Change>,Error> {
+    let file = match File::open(get_path()) {
+        Err(_) => return Ok(Vec::new()),
+        Ok(file) => file,
+    };
+
+    let mut changes : Vec<Change> = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() { continue; }
+        match serde_json::from_str(&line) {
+            Ok(change) => changes.push(change),
+            Err(error) => warn!("Skipping unreadable history entry: {}",error),
+        }
+    }
+
+    Ok(changes)
+}
+
+fn save(changes : &[Change]) -> Result<(),Error> {
+    let mut file = File::create(get_path())?;
+    for change in changes {
+        writeln!(file,"{}",serde_json::to_string(change)?)?;
+    }
+    Ok(())
+}
+
+fn app_name() -> String {
+    //! the name of the running executable, used to know which
+    //! lovepack app made the change.
+
+    env::current_exe().ok()
+        .and_then(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
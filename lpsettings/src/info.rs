@@ -0,0 +1,164 @@
+//! where the settings are kept and which versions are in use.
+//!
+//! every file and folder is listed with whether it exists and if it
+//! can be read and written, so it's clear why a setting isn't being
+//! picked up or can't be saved.
+
+use std::fs::{ self, File, OpenOptions };
+use std::path::{ Path, PathBuf };
+use std::process;
+
+use failure::Error;
+
+use map;
+use system;
+use backup;
+use profile;
+use project;
+use { get_file_global, get_file_local, project_root };
+
+/// everything `lpsettings paths` shows
+#[derive(Serialize)]
+pub struct Info {
+    pub version : String,
+    /// the version of the options map, the keys this build knows about
+    pub map_version : String,
+    pub profile : Option<String>,
+    /// the project the current directory is in
+    pub project : Option<String>,
+    pub paths : Vec<PathInfo>,
+}
+
+/// a file or folder that is used
+#[derive(Serialize)]
+pub struct PathInfo {
+    /// what it is, `global`, `local`, `inherits`, `system` or `backups`
+    pub name : String,
+    /// `None` if there isn't one, like the local file outside a project
+    pub path : Option<String>,
+    pub exists : bool,
+    pub readable : bool,
+    /// if it can be written to, or created when it doesn't exist
+    pub writable : bool,
+    /// why the settings in it can't be read, like a toml error
+    pub error : Option<String>,
+}
+
+impl PathInfo {
+    pub fn new<P : AsRef<Path>>(name : &str, path : P) -> PathInfo {
+        let path = path.as_ref();
+        let exists = path.exists();
+
+        PathInfo {
+            name : name.to_string(),
+            path : Some(path.display().to_string()),
+            exists,
+            readable : exists && is_readable(path),
+            writable : is_writable(path),
+            error : None,
+        }
+    }
+
+    pub fn settings<P : AsRef<Path>>(name : &str, path : P) -> PathInfo {
+        //! a settings file, which is also read to check the settings
+        //! in it can be used
+
+        let mut info = PathInfo::new(name,path.as_ref());
+        if info.readable {
+            info.error = project::load(path.as_ref()).err().map(|error| error.to_string());
+        }
+        info
+    }
+
+    pub fn none(name : &str) -> PathInfo {
+        PathInfo { name : name.to_string(), path : None, exists : false, readable : false, writable : false, error : None }
+    }
+
+    pub fn status(&self) -> String {
+        //! a short description of the permissions, or why the file
+        //! can't be used
+
+        if let Some(ref error) = self.error {
+            return format!("unreadable: {}",error);
+        }
+
+        match (self.path.is_some(), self.exists, self.readable, self.writable) {
+            (false, _, _, _) => "none found",
+            (true, false, _, true) => "missing, can be created",
+            (true, false, _, false) => "missing, can't be created",
+            (true, true, false, _) => "can't be read",
+            (true, true, true, false) => "read only",
+            (true, true, true, true) => "read and write",
+        }.to_string()
+    }
+}
+
+pub fn collect(profile : Option<&str>) -> Result<Info,Error> {
+    //! looks up where everything is, `profile` is the one that was
+    //! asked for, if any.
+    //!
+    //! files that can't be read don't make this fail, they are listed
+    //! with the error, which is when this is needed the most.
+
+    let root = project_root();
+
+    let mut paths : Vec<PathInfo> = Vec::new();
+    paths.push(PathInfo::settings("global",get_file_global(false)));
+    match root {
+        Some(_) => paths.push(PathInfo::settings("local",get_file_local(false))),
+        None => paths.push(PathInfo::none("local")),
+    }
+    for file in project::find_files() {
+        if file.parent() != root.as_deref() {
+            paths.push(PathInfo::settings("inherits",file));
+        }
+    }
+    paths.push(PathInfo::settings("system",system::get_file()));
+    paths.push(PathInfo::new("backups",backup::get_backup_folder()));
+
+    Ok(Info {
+        version : env!("CARGO_PKG_VERSION").to_string(),
+        map_version : map::get_version()?.to_string(),
+        profile : match profile {
+            Some(profile) => Some(profile.to_string()),
+            // unknown when the settings can't be read
+            None => profile::get_active().unwrap_or(None),
+        },
+        project : root.map(|root| root.display().to_string()),
+        paths,
+    })
+}
+
+fn is_readable(path : &Path) -> bool {
+    if path.is_dir() { fs::read_dir(path).is_ok() }
+    else { File::open(path).is_ok() }
+}
+
+fn is_writable(path : &Path) -> bool {
+    //! checks by trying, the permission bits don't say who the
+    //! current user is. nothing is changed, files are opened without
+    //! truncating and folders only get a file that is removed again.
+
+    if path.is_file() {
+        return OpenOptions::new().append(true).open(path).is_ok();
+    }
+
+    if path.is_dir() {
+        let probe = path.join(format!(".lpsettings-check-{}",process::id()));
+        return match OpenOptions::new().write(true).create_new(true).open(&probe) {
+            Ok(_) => { fs::remove_file(&probe).ok(); true },
+            Err(_) => false,
+        };
+    }
+
+    // doesn't exist yet, it can be created if the closest folder that
+    // does exist can be written to
+    match closest_existing(path) {
+        Some(folder) => folder.is_dir() && is_writable(&folder),
+        None => false,
+    }
+}
+
+fn closest_existing(path : &Path) -> Option<PathBuf> {
+    path.ancestors().skip(1).find(|folder| folder.exists()).map(|folder| folder.to_path_buf())
+}
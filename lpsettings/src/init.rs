@@ -0,0 +1,314 @@
+//! initializing the global or local settings.
+//!
+//! by default every key marked `init` in the options map is asked
+//! for on the terminal, or every key marked `project` when creating
+//! the local settings file for a project. the answers can also be
+//! given up front, from a settings file (`--from`) or as `key=value`
+//! pairs (`--set`), and `--yes` takes the defaults from the options
+//! map for the rest, so the settings can be provisioned without
+//! anyone at the terminal.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{ Path, PathBuf };
+
+use failure::Error;
+
+use error::ErrorKind;
+use map::{ self, OptionsMap };
+use value;
+use diff;
+use secrets;
+use history;
+use theme;
+use prompt::Prompter;
+use project;
+use { Type, load_settings, save_settings, get_file_global };
+
+#[derive(Default)]
+pub struct Options {
+    desc : bool,
+    yes : bool,
+    from : Option<PathBuf>,
+    set : Vec<(String,String)>,
+    local : bool,
+    force : bool,
+}
+
+impl Options {
+    pub fn new() -> Options {
+        Options::default()
+    }
+
+    pub fn desc(mut self, desc : bool) -> Options {
+        //! adds the descriptions from the options map to the questions
+        self.desc = desc;
+        self
+    }
+
+    pub fn yes(mut self, yes : bool) -> Options {
+        //! doesn't ask anything, using the default for keys that
+        //! aren't set and weren't given an answer
+        self.yes = yes;
+        self
+    }
+
+    pub fn from<P : AsRef<Path>>(mut self, path : P) -> Options {
+        //! takes the answers from a settings file
+        self.from = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn set(mut self, key : &str, value : &str) -> Options {
+        //! answers a single key, these win over the `from` file
+        self.set.push((key.to_string(),value.to_string()));
+        self
+    }
+
+    pub fn local(mut self, local : bool) -> Options {
+        //! creates the local settings file for the project instead,
+        //! in the current directory if it isn't in a project yet
+        self.local = local;
+        self
+    }
+
+    pub fn force(mut self, force : bool) -> Options {
+        //! initializes the local settings file even if it exists
+        self.force = force;
+        self
+    }
+
+    fn is_interactive(&self) -> bool {
+        !self.yes && self.from.is_none() && self.set.is_empty()
+    }
+}
+
+pub fn parse_set(input : &str) -> Result<(String,String),Error> {
+    //! splits a `key=value` pair
+
+    match input.find('=') {
+        Some(index) if index > 0 => Ok((input[..index].trim().to_string(),input[index+1..].trim().to_string())),
+        _ => Err(ErrorKind::InvalidInput(format!("'{}' should be in the form key=value",input)).into()),
+    }
+}
+
+pub fn run<P : Prompter>(options : &Options, prompter : &mut P) -> Result<Vec<(String,Type)>,Error> {
+    //! initializes the global or local settings, asking the prompter
+    //! for anything that wasn't answered, returns the keys and values
+    //! that were written.
+    //!
+    //! if asking and the user leaves the entry blank it will not be
+    //! edited or created, so the existing values will stay the same or
+    //! new values will not be created.
+
+    let interactive = options.is_interactive();
+    if interactive && !prompter.is_interactive() {
+        return Err(ErrorKind::InvalidInput("Cannot ask for the settings, stdin isn't a terminal. Use --yes, --from FILE or --set KEY=VALUE".to_string()).into());
+    }
+
+    if !options.local { return initialize(options,prompter,None); }
+
+    // the project is made in the current directory, even inside
+    // another project, which it then inherits from
+    let folder = env::current_dir()?;
+    let file = project::get_file_in(&folder,false);
+    if !options.force && file.exists() {
+        return Err(ErrorKind::InvalidInput(format!("There already is a local settings file, {}. Use --force to initialize it again",file.display())).into());
+    }
+
+    initialize(options,prompter,Some(&folder))
+}
+
+fn initialize<P : Prompter>(options : &Options, prompter : &mut P, project : Option<&Path>) -> Result<Vec<(String,Type)>,Error> {
+    //! asks for and saves the settings, the global ones or the local
+    //! ones of the project in the given folder
+
+    let interactive = options.is_interactive();
+    let local = project.is_some();
+    let scope = if local { "local" } else { "global" };
+
+    let map = map::create_options_map()?;
+    let mut answers = get_answers(&map,options)?;
+
+    let (mut settings, mut secret_store) = match project {
+        Some(folder) => (project::load_in(folder,false)?, project::load_in(folder,true)?),
+        None => (load_settings(false)?, load_settings(true)?),
+    };
+    let mut init_secret = false;
+
+    if interactive {
+        prompter.say(&format!("Initializing {} settings, leave empty to keep existing / not set.",
+            if local { "the project's" } else { "the" }));
+    }
+
+    // the keys to initialize, then any other keys that were answered
+    let mut keys : Vec<String> = get_init_keys(&map,local).iter().map(|m| m.key.to_string()).collect();
+    keys.extend(answers.keys().filter(|key| !keys.contains(key)).cloned().collect::<Vec<String>>());
+
+    // everything is decided before anything is saved, so it can be
+    // reviewed first
+    let mut pending : Vec<(String,Option<Type>,Type)> = Vec::new();
+    for key in keys {
+        let store = if secrets::is_secret(&key) { &secret_store } else { &settings };
+        let old_value = if local { store.get_value_local(&key) } else { store.get_value_global(&key) };
+
+        let new_value = if let Some(answer) = answers.remove(&key) {
+            Some(answer)
+        } else if interactive {
+            ask(prompter,&map,&key,&old_value,options.desc)?
+        } else if options.yes && old_value.is_none() {
+            map.iter().find(|m| m.key == key).and_then(|m| m.default.clone())
+        } else {
+            None
+        };
+
+        if let Some(new_value) = new_value {
+            if old_value.as_ref() != Some(&new_value) { pending.push((key,old_value,new_value)); }
+        }
+    }
+
+    if interactive && !pending.is_empty() && !review(prompter,&pending)? {
+        prompter.say("Nothing was saved");
+        return Ok(Vec::new());
+    }
+
+    let mut written : Vec<(String,Type)> = Vec::new();
+    let mut changes : Vec<history::Change> = Vec::new();
+
+    for (key,old_value,new_value) in pending {
+        let secret = secrets::is_secret(&key);
+        let store = if secret { &mut secret_store } else { &mut settings };
+        if local { store.set_value_local(&key,&new_value)?; } else { store.set_value_global(&key,&new_value)?; }
+        init_secret = init_secret || secret;
+
+        let file = match project {
+            Some(folder) => project::get_file_in(folder,secret).display().to_string(),
+            None => get_file_global(secret),
+        };
+        changes.push(history::Change::new("init",scope,&file,
+            &key,old_value,Some(new_value.clone())));
+        written.push((key,new_value));
+    }
+
+    // saves the new settings
+    match project {
+        Some(folder) => {
+            project::save_in(&settings,folder,false)?;
+            if init_secret { project::save_in(&secret_store,folder,true)?; }
+        },
+        None => {
+            save_settings(&settings,false)?;
+            if init_secret { save_settings(&secret_store,true)?; }
+        },
+    }
+    history::record(&changes);
+
+    Ok(written)
+}
+
+fn ask<P : Prompter>(prompter : &mut P, map : &[OptionsMap], key : &str, old_value : &Option<Type>, desc : bool) -> Result<Option<Type>,Error> {
+    //! asks for the key's value in the way that suits its type, asking
+    //! again until the answer is valid. secret values are hidden,
+    //! switches are yes or no and keys with choices are a menu.
+
+    let question = get_question(map,key,old_value,desc);
+    let kind = map::get_kind(map,key);
+
+    if kind == Some("switch") {
+        let default = match old_value.as_ref().or_else(|| map.iter().find(|m| m.key == key).and_then(|m| m.default.as_ref())) {
+            Some(Type::Switch(switch)) => *switch,
+            _ => false,
+        };
+        // an empty answer keeps the old value, or leaves it unset
+        return Ok(prompter.confirm_optional(&question,default)?.map(Type::Switch));
+    }
+
+    let choices = map::get_choices(map,key);
+    if !choices.is_empty() {
+        return match prompter.choose(&question,choices)? {
+            Some(index) => Ok(Some(value::parse_as(kind.unwrap_or("text"),&choices[index])?)),
+            None => Ok(None),
+        };
+    }
+
+    loop {
+        let input = if secrets::is_secret(key) { prompter.ask_hidden(&question)? } else { prompter.ask(&question)? };
+        if input.is_empty() { return Ok(None); }
+
+        match value::parse_as(kind.unwrap_or("text"),&input) {
+            Ok(value) => return Ok(Some(value)),
+            Err(error) => prompter.say(&theme::error_message(error.to_string())),
+        }
+    }
+}
+
+fn review<P : Prompter>(prompter : &mut P, pending : &[(String,Option<Type>,Type)]) -> Result<bool,Error> {
+    //! shows the changes that are about to be saved, and asks if
+    //! they should be
+
+    prompter.say(&theme::heading("Changes"));
+    for (key,old_value,new_value) in pending {
+        let old_value = match old_value {
+            Some(old_value) => theme::key_value(format!("{}",secrets::mask(key,old_value.clone()))),
+            None => theme::comment("(not set)"),
+        };
+        prompter.say(&format!("  {}: {} -> {}",
+            theme::key(key),
+            old_value,
+            theme::key_value_set(format!("{}",secrets::mask(key,new_value.clone())))));
+    }
+
+    prompter.confirm("Save these changes?",true)
+}
+
+fn get_init_keys(map : &[OptionsMap], local : bool) -> Vec<&OptionsMap> {
+    //! the keys marked `init`, or `project` for the local settings,
+    //! that are valid in this version
+
+    map.iter()
+        .filter(|m| if local { m.project == Some(true) } else { m.init == Some(true) })
+        .filter(|m| map::is_current(m))
+        .collect()
+}
+
+fn get_answers(map : &[OptionsMap], options : &Options) -> Result<BTreeMap<String,Type>,Error> {
+    //! the answers from the `from` file and `set` pairs, checked
+    //! against the options map.
+
+    let mut answers : BTreeMap<String,Type> = BTreeMap::new();
+
+    if let Some(ref path) = options.from {
+        let values = diff::load_file(path)
+            .map_err(|error| error.context(format!("Cannot read the answers from {}",path.display())))?;
+        answers.extend(values);
+    }
+
+    for (key,input) in options.set.iter() {
+        answers.insert(key.to_string(),value::parse_for(map,key,None,input)?);
+    }
+
+    for (key,answer) in answers.iter() {
+        value::check(map,key,answer)?;
+    }
+
+    Ok(answers)
+}
+
+fn get_question(map : &[OptionsMap], key : &str, old_value : &Option<Type>, desc : bool) -> String {
+    let mut question = if let Some(ref old_value) = old_value {
+        format!("{}({})",
+            theme::key(key),
+            theme::key_value(format!("{}",secrets::mask(key,old_value.clone()))))
+    } else {
+        theme::key(key)
+    };
+
+    // adds the descriptions if requested.
+    if desc {
+        if let Some(desc) = map::get_desc(map,key) {
+            question = format!("{} - {}",question,theme::comment(desc));
+        }
+    }
+
+    question
+}
@@ -2,131 +2,642 @@
 //! provides a ready made CLAP app for easy integration
 
 use clap;
-use location::Location;
+use console;
+use scope::Scope;
 use theme;
-use std::env;
+use history;
+use error::ErrorKind;
+use output;
+use completion;
+use tui;
+use edit;
+use diff;
+use backup;
+use list;
+use init;
+use prompt;
+use output::OutputFormat;
+use store::{ Store, Source };
+use profile;
+use secrets;
+use crypt;
+use info;
+use map;
+use value;
+use serde_json;
+use toml;
+use Type;
+use std::io::{ self, Write };
 use failure::Error;
 
-pub fn process(matches : &clap::ArgMatches) -> Result<(),Error> {
+/// what `process` did, so apps embedding the subcommand don't need to
+/// read it back out of the output
+#[derive(Debug,Default,PartialEq)]
+pub struct Outcome {
+    /// the subcommand that was run, `get` or `set` when only a key is
+    /// given, empty if there was nothing to do
+    pub command : String,
+    /// the value that was read by `get`
+    pub value : Option<Type>,
+    /// the keys that were changed
+    pub changed : Vec<String>,
+}
+
+impl Outcome {
+    fn new(command : &str) -> Outcome {
+        Outcome { command : command.to_string(), .. Outcome::default() }
+    }
+
+    fn changed(mut self, changed : Vec<String>) -> Outcome {
+        self.changed = changed;
+        self
+    }
+}
+
+pub fn process(matches : &clap::ArgMatches) -> Result<Outcome,Error> {
     //! process function to be used with [CLAP.RS](https://clap.rs/)'s `.get_matches()`.
     //!
     //! should be called with the subset of matches from clap's `.get_matches()` if used as a subcommand, or all the matches if used as the main app.
     //!
-    //! ```rust
+    //! ```rust,no_run
+    //! # extern crate clap;
+    //! # extern crate lpsettings;
+    //! # use lpsettings::interface;
+    //! # fn main() {
+    //! # let app = clap::App::new("newapp")
+    //! #     .subcommand(interface::app().name("settings"))
+    //! #     .get_matches();
     //! // example of using as a subcommand, this is called after .get_matches() 
     //! match app.subcommand() {
     //!     ("settings", Some(matches)) => { interface::process(matches); },
     //!     _ => {},
     //! }
+    //! # }
     //! ```
 
+    process_to(matches,&mut io::stdout())
+}
+
+pub fn process_to<W : Write>(matches : &clap::ArgMatches, out : &mut W) -> Result<Outcome,Error> {
+    //! the same as `process`, but everything that would be printed is
+    //! written to `out`, so it can be captured or checked. only the
+    //! interactive subcommands (`init` questions, `edit` and `tui`)
+    //! still use the terminal.
+
+    let result = run_to(matches,out);
+
+    // found while reading the secrets, written even if the command failed
+    for warning in secrets::take_warnings() {
+        writeln!(out,"{} {}",theme::warning("Warning:"),warning)?;
+    }
+
+    result
+}
+
+fn run_to<W : Write>(matches : &clap::ArgMatches, out : &mut W) -> Result<Outcome,Error> {
+    let mut outcome = Outcome::default();
+
     // checks where it should perform the commands. these are not compatible so 
     // both should never run at the same time.
-    if matches.is_present("local") { env::set_var("LOVEPACK_SETTINGS_LOCATION","local"); }
-    if matches.is_present("global") { env::set_var("LOVEPACK_SETTINGS_LOCATION","global"); }
+    let scope = Scope::from_flags(matches.is_present("local"),matches.is_present("global"),matches.is_present("system"));
+
+    // colors, the tui uses them too
+    if let Some(color) = matches.value_of("color") { theme::set_color(color.parse()?); }
+    console::set_colors_enabled(theme::colors_enabled());
+    console::set_colors_enabled_stderr(theme::colors_enabled_on(theme::Stream::Stderr));
+
+    // the profile to use for everything in this run
+    let selected = matches.value_of("profile");
     
     // checks if it should run the initalization instead
     if let Some(init_matches) = matches.subcommand_matches("init") { 
-        let desc = if init_matches.is_present("desc") { true } else { false };
-        super::initalize(desc)?; 
+        outcome = Outcome::new("init").changed(initalize(out,init_matches,matches.is_present("local"))?);
+    }
+
+    // removing a value
+    if let Some(unset_matches) = matches.subcommand_matches("unset") {
+        if let Some(key) = unset_matches.value_of("KEY") {
+            check_writable(scope,matches)?;
+            unset_value(out,key,scope)?;
+            outcome = Outcome::new("unset").changed(vec![key.to_string()]);
+        }
+    }
+
+    // importing another settings file
+    if let Some(import_matches) = matches.subcommand_matches("import") {
+        if let Some(path) = import_matches.value_of("FILE") {
+            check_writable(scope,matches)?;
+            outcome = Outcome::new("import").changed(import(out,path,scope)?);
+        }
+    }
+
+    // change history
+    if let Some(history_matches) = matches.subcommand_matches("history") {
+        display_history(out,history_matches.value_of("KEY"))?;
+        outcome = Outcome::new("history");
+    }
+
+    if let Some(undo_matches) = matches.subcommand_matches("undo") {
+        let count = match undo_matches.value_of("COUNT") {
+            None => 1,
+            Some(count) => count.parse::<usize>()
+                .map_err(|_| ErrorKind::InvalidInput(format!("'{}' is not a valid number of changes",count)))?,
+        };
+        outcome = Outcome::new("undo").changed(undo(out,count)?);
+    }
+
+    // backups
+    if let Some(backup_matches) = matches.subcommand_matches("backup") {
+        if backup_matches.is_present("list") {
+            for path in backup::list()? {
+                writeln!(out,"{}",path.file_name().unwrap_or_default().to_string_lossy())?;
+            }
+        } else {
+            if scope == Scope::System {
+                return Err(ErrorKind::InvalidInput("Backups are only made of the global or local settings".to_string()).into());
+            }
+            let path = backup::create(scope.is_local())?;
+            writeln!(out,"Backed up to {}",theme::key(path.display().to_string()))?;
+        }
+        outcome = Outcome::new("backup");
+    }
+
+    // profiles
+    if let Some(profile_matches) = matches.subcommand_matches("profile") {
+        process_profile(out,profile_matches,selected)?;
+        outcome = Outcome::new("profile");
+    }
+
+    // how values are written out
+    let format = match matches.value_of("format") {
+        Some(format) => format.parse::<OutputFormat>()?,
+        None => OutputFormat::Text,
+    };
+
+    // editing the file
+    if matches.subcommand_matches("edit").is_some() {
+        check_writable(scope,matches)?;
+        let changed = edit::run(scope)?;
+        if changed.is_empty() {
+            writeln!(out,"{}{}",scope.to_string_cap(),if scope == Scope::Best { "No changes" } else { " no changes" })?;
+        } else {
+            writeln!(out,"{}",theme::success(format!("{}{}",scope.to_string_cap(),
+                if scope == Scope::Best { "Saved the settings" } else { " saved the settings" })))?;
+        }
+        outcome = Outcome::new("edit").changed(changed);
+    }
+
+    // full screen editor
+    if matches.subcommand_matches("tui").is_some() {
+        if scope == Scope::System {
+            return Err(ErrorKind::InvalidInput("The editor only opens the global or local settings, use `--system edit`".to_string()).into());
+        }
+        tui::run(scope.is_local())?;
+        outcome = Outcome::new("tui");
+    }
+
+    // showing the theme
+    if let Some(theme_matches) = matches.subcommand_matches("theme") {
+        display_theme(out,theme_matches.value_of("PRESET"))?;
+        outcome = Outcome::new("theme");
+    }
+
+    // where the settings are kept
+    if matches.subcommand_matches("paths").is_some() {
+        display_info(out,format,selected)?;
+        outcome = Outcome::new("paths");
+    }
+
+    // shell completion
+    if let Some(completions_matches) = matches.subcommand_matches("completions") {
+        let shell = completions_matches.value_of("SHELL").unwrap_or("bash");
+        completion::generate(&mut app(),shell,out)?;
+        outcome = Outcome::new("completions");
+    }
+
+    if let Some(complete_matches) = matches.subcommand_matches("complete") {
+        let candidates = match complete_matches.subcommand() {
+            ("values", Some(values_matches)) => completion::values(values_matches.value_of("KEY").unwrap_or(""))?,
+            _ => completion::keys()?,
+        };
+        for candidate in candidates {
+            writeln!(out,"{}",candidate)?;
+        }
+        outcome = Outcome::new("complete");
+    }
+
+    // comparing settings
+    if let Some(diff_matches) = matches.subcommand_matches("diff") {
+        let changes = match (diff_matches.value_of("FILE"), diff_matches.value_of("backup")) {
+            (Some(path), _) => diff::file(path,&scope)?,
+            (None, Some(name)) => diff::backup(name)?,
+            (None, None) => diff::scopes()?,
+        };
+        diff::write(out,format,&changes)?;
+        outcome = Outcome::new("diff");
     }
 
     // list options
     if let Some(list_matches) = matches.subcommand_matches("list") {
+        let mut options = list::Options::new(format)
+            .tree(list_matches.is_present("tree"))
+            .with_desc(list_matches.is_present("with-desc"))
+            .effective(list_matches.is_present("effective"))
+            .scope(scope)
+            .profile(selected);
+        if let Some(filter) = list_matches.value_of("FILTER") {
+            options = options.filter(filter);
+        }
+
         if list_matches.is_present("possible") {
-            super::list_possible()?;
+            list::possible(out,&options)?;
         } else {
-            super::list_current()?;
+            list::current(out,&options)?;
         }
+        outcome = Outcome::new("list");
     }
 
-    let mut new_value : Option<String> = None;
-    let mut key : Option<String> = None;
-
-    if let Some(in_key) = matches.value_of("KEY") { key = Some(in_key.to_string()); }
-    if let Some(in_value) = matches.value_of("VALUE") { new_value = Some(in_value.to_string()); }
-
     // checks based on the Options if it needs to get or set a value.
-    if key.is_some() && new_value.is_none() { display_value(key.as_ref().unwrap()); }
-    if key.is_some() && new_value.is_some() { set_value(key.as_ref().unwrap(),new_value.as_ref().unwrap()); }
+    match (matches.value_of("KEY"), matches.value_of("VALUE")) {
+        (Some(key), None) => {
+            outcome = Outcome::new("get");
+            outcome.value = Some(display_value(out,key,scope,selected,format,matches.is_present("reveal"),matches.is_present("no-expand"))?);
+        },
+        (Some(key), Some(new_value)) => {
+            // when a profile is given the value is written into that profile
+            let key = match selected {
+                Some(name) => format!("{}.{}",profile::get_key(name),key),
+                None => key.to_string(),
+            };
+            check_writable(scope,matches)?;
+            set_value(out,&key,new_value,scope,matches.is_present("encrypt"))?;
+            outcome = Outcome::new("set").changed(vec![key]);
+        },
+        _ => { },
+    }
 
     // success!
-    Ok( () )
+    Ok(outcome)
+}
+
+pub fn display_error(error : &Error) {
+    //! writes the error, and everything that caused it, to stderr.
+    //!
+    //! use with [`error::exit_code`](../error/fn.exit_code.html) to
+    //! exit with a code that scripts can check.
+
+    write_error_on(&mut io::stderr(),error,theme::Stream::Stderr).ok();
+}
+
+pub fn write_error<W : Write>(out : &mut W, error : &Error) -> Result<(),Error> {
+    //! writes the error, and everything that caused it, to `out`
+
+    write_error_on(out,error,theme::Stream::Stdout)
 }
 
-fn display_value(key : &str) {
+fn write_error_on<W : Write>(out : &mut W, error : &Error, stream : theme::Stream) -> Result<(),Error> {
+    //! writes the error, colored if `stream` gets colors
+
+    let message : Vec<String> = error.iter_chain()
+        .map(|cause| cause.to_string())
+        .collect();
+
+    let theme = theme::get_theme();
+    writeln!(out,"{} {}",
+        theme.paint_on(stream,theme::Role::Error,"error:"),
+        theme.paint_on(stream,theme::Role::Error,&message.join(": "))
+    )?;
+
+    Ok(())
+}
+
+fn display_info<W : Write>(out : &mut W, format : OutputFormat, profile : Option<&str>) -> Result<(),Error> {
+    //! writes where each of the settings files is, if it can be read
+    //! and written, and the versions in use, internal to interface.rs
+
+    let info = info::collect(profile)?;
+
+    match format {
+        OutputFormat::Json => return Ok(writeln!(out,"{}",serde_json::to_string_pretty(&info)?)?),
+        OutputFormat::Toml => return Ok(write!(out,"{}",toml::to_string(&toml::Value::try_from(&info)?)?)?),
+        OutputFormat::Raw => {
+            for path in info.paths.iter() {
+                writeln!(out,"{}={}",path.name,path.path.as_deref().unwrap_or(""))?;
+            }
+            return Ok(());
+        },
+        OutputFormat::Text => { },
+    }
+
+    writeln!(out,"{}: {}",theme::key("version"),theme::key_value(&info.version))?;
+    writeln!(out,"{}: {}",theme::key("options map"),theme::key_value(&info.map_version))?;
+    writeln!(out,"{}: {}",theme::key("profile"),match info.profile {
+        Some(ref profile) => theme::key_value(profile),
+        None => theme::comment("none"),
+    })?;
+    writeln!(out,"{}: {}",theme::key("project"),match info.project {
+        Some(ref project) => theme::key_value(project),
+        None => theme::comment("none found, using the current directory"),
+    })?;
+
+    for path in info.paths.iter() {
+        let status = if path.error.is_some() || (path.path.is_some() && !path.writable) { theme::warning(path.status()) } else { theme::comment(path.status()) };
+        match path.path {
+            Some(ref file) => writeln!(out,"{}: {} ({})",theme::key(&path.name),theme::key_value(file),status)?,
+            None => writeln!(out,"{}: {}",theme::key(&path.name),status)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn check_writable(scope : Scope, matches : &clap::ArgMatches) -> Result<(),Error> {
+    //! the system settings are shared by every user, so they are only
+    //! changed when `--system` is given, not from the environmental
+    //! variable.
+
+    if scope == Scope::System && !matches.is_present("system") {
+        return Err(ErrorKind::InvalidInput("The system settings can only be changed with --system".to_string()).into());
+    }
+    Ok(())
+}
+
+fn display_value<W : Write>(out : &mut W, key : &str, location : Scope, profile : Option<&str>, format : OutputFormat, reveal : bool, raw : bool) -> Result<Type,Error> {
     //! displays the value to the cli, internal to interface.rs
+    //! 
+    //! secret values are masked unless `reveal` is set, along with
+    //! any secret value that is referenced. references are not
+    //! expanded if `raw` is set.
     
-    // checks where we want to read this value
-    let location = Location::get_location();
-    let value = match location {
-        Location::Local => super::get_value_local(key),
-        Location::Global => super::get_value_global(key),
-        Location::Best => super::get_value(key),
+    let store = Store::load()?.profile(profile);
+    let value = if raw { 
+        store.get_value_raw(key,&location) 
+    } else if reveal { 
+        store.get_value(key,&location) 
+    } else {
+        store.get_value_masked(key,&location)
+    };
+
+    let value = match value {
+        Err(error) => return Err(error.context(format!("Error reading setting '{}'",key)).into()),
+        Ok(None) => return Err(ErrorKind::KeyNotFound(key.to_string()).into()),
+        Ok(Some(value)) => value,
     };
 
     // the output
-    match value {
-        Err(error) => {
-            println!("Error reading setting \'{}\': {} {}",
-                theme::key(key),
-                theme::error_message(error.to_string()),
-                location
-            ); 
-        },
-        Ok(value) => {
-            match value {
-                Some(value) => { 
-                    println!("{}: {} {}",
-                        theme::key(key),
-                        theme::key_value(&format!("{}",value)),
-                        location
-                    );
-                },
-                None => {
-                    println!("{} is not defined",
-                        theme::error(key)
-                    ); 
-                }
-            }
-        }    
+    if format != OutputFormat::Text {
+        let source = match location {
+            Scope::Best => store.get_source(key)?.unwrap_or(Source::Global),
+            Scope::Local => Source::Local,
+            Scope::Global => Source::Global,
+            Scope::System => Source::System,
+        };
+        let entry = if reveal { 
+            output::Entry::new_revealed(key,Some(value.clone()),source.name())
+        } else { 
+            output::Entry::new(key,Some(value.clone()),source.name())
+        };
+        output::write_entry(out,format,&entry)?;
+        return Ok(value);
     }
+
+    let shown = if reveal { value.clone() } else { secrets::mask(key,value.clone()) };
+    writeln!(out,"{}: {} {}",
+        theme::key(key),
+        theme::key_value(format!("{}",shown)),
+        location
+    )?;
+
+    Ok(value)
 }
 
-fn set_value(key : &str, value: &str) {
+fn set_value<W : Write>(out : &mut W, key : &str, value : &str, location : Scope, encrypt : bool) -> Result<(),Error> {
     //! sets the value, internal to interface.rs
 
+    // the key file is the user's, nobody else could read it
+    if encrypt && location == Scope::System {
+        return Err(ErrorKind::InvalidInput(format!("'{}' can't be encrypted system-wide, only the user's own settings can be",key)).into());
+    }
+
+    // read the same way as in the tui, by the key's type
+    let current = super::get_value_raw_in(key,location)?;
+    let value = value::parse_for(map::get_options_map(),key,current.as_ref(),value)
+        .map_err(|error| error.context(format!("Error setting '{}'",key)))?;
+
     // chooses where to write the settings
-    let location = Location::get_location();    
-    let result = match location {
-        Location::Local => super::set_value_local(&key,&value),
-        _ => super::set_value(&key,&value)
+    let result = match (location, encrypt) {
+        (_, false) => super::set_value_in(key,&value,location),
+        (Scope::Local, true) => super::set_value_local_encrypted(key,&value),
+        (_, true) => super::set_value_encrypted(key,&value),
+    };
+
+    let old_value = result
+        .map_err(|error| error.context(format!("Error setting '{}'",key)))?;
+
+    let old = match old_value {
+        Some(old_value) => format!("overwriting {}",theme::key_value(display_stored(key,old_value))),
+        None => String::new(),
+    };
+    
+    writeln!(out,"{}{} {} to {}{} {}",
+        location.to_string_cap(),
+        if location == Scope::Best { "Set" } else { " set" },
+        theme::key(key),
+        theme::key_value_set(format!("{}",secrets::mask(key,value.clone()))),
+        if encrypt { " encrypted" } else { "" },
+        old
+    )?;
+
+    Ok(())
+}
+
+fn unset_value<W : Write>(out : &mut W, key : &str, location : Scope) -> Result<(),Error> {
+    //! removes the value, internal to interface.rs
+
+    let old_value = match super::unset_value_in(key,location) {
+        Err(error) => return Err(error.context(format!("Error removing '{}'",key)).into()),
+        Ok(None) => return Err(ErrorKind::KeyNotFound(key.to_string()).into()),
+        Ok(Some(old_value)) => old_value,
     };
 
-    match result { 
-        Ok(old_value) => { 
-            let old = match old_value {
-                Some(old_value) => format!("overwriting {}",theme::key_value(format!("{}",old_value))),
-                None => format!(""),
+    writeln!(out,"{}{} {}, was {}",
+        location.to_string_cap(),
+        if location == Scope::Best { "Removed" } else { " removed" },
+        theme::key(key),
+        theme::key_value(display_stored(key,old_value))
+    )?;
+
+    Ok(())
+}
+
+fn import<W : Write>(out : &mut W, path : &str, location : Scope) -> Result<Vec<String>,Error> {
+    //! imports a settings file, internal to interface.rs
+
+    let keys = super::import_in(path,location)
+        .map_err(|error| error.context(format!("Error importing '{}'",path)))?;
+
+    for key in keys.iter() {
+        writeln!(out,"{}{} {}",
+            location.to_string_cap(),
+            if location == Scope::Best { "Imported" } else { " imported" },
+            theme::key(key)
+        )?;
+    }
+
+    Ok(keys)
+}
+
+fn initalize<W : Write>(out : &mut W, matches : &clap::ArgMatches, local : bool) -> Result<Vec<String>,Error> {
+    //! initializes the settings, asking or from the answers given,
+    //! internal to interface.rs
+
+    let mut options = init::Options::new()
+        .desc(matches.is_present("desc"))
+        .yes(matches.is_present("yes"))
+        .local(local || matches.is_present("local"))
+        .force(matches.is_present("force"));
+    if let Some(path) = matches.value_of("from") { options = options.from(path); }
+    if let Some(pairs) = matches.values_of("set") {
+        for pair in pairs {
+            let (key,value) = init::parse_set(pair)?;
+            options = options.set(&key,&value);
+        }
+    }
+
+    let written = init::run(&options,&mut prompt::Terminal::new())?;
+    for (key,value) in written.iter() {
+        writeln!(out,"{} {} {}",
+            theme::key(key),
+            theme::comment("="),
+            theme::key_value_set(format!("{}",secrets::mask(key,value.clone())))
+        )?;
+    }
+    match written.len() {
+        0 => writeln!(out,"Nothing was written")?,
+        1 => writeln!(out,"Wrote 1 setting")?,
+        count => writeln!(out,"Wrote {} settings",count)?,
+    }
+
+    Ok(written.into_iter().map(|(key,_)| key).collect())
+}
+
+fn display_history<W : Write>(out : &mut W, key : Option<&str>) -> Result<(),Error> {
+    //! prints the change journal, internal to interface.rs
+
+    for change in history::get_changes(key)? {
+        writeln!(out,"{} {} {} {} {}: {} -> {}",
+            theme::comment(&change.timestamp),
+            change.app,
+            change.action,
+            change.scope,
+            theme::key(&change.key),
+            display_option(&change.old),
+            theme::key_value_set(display_option(&change.new))
+        )?;
+    }
+
+    Ok(())
+}
+
+fn display_theme<W : Write>(out : &mut W, preset : Option<&str>) -> Result<(),Error> {
+    //! shows each role in the theme's style, internal to interface.rs
+
+    let theme = match preset {
+        Some(preset) => theme::Theme::preset(preset)?,
+        None => theme::get_theme(),
+    };
+
+    for role in theme::Role::all().iter() {
+        writeln!(out,"{}",theme.paint(*role,role.name()))?;
+    }
+
+    Ok(())
+}
+
+fn undo<W : Write>(out : &mut W, count : usize) -> Result<Vec<String>,Error> {
+    //! reverts the last changes, internal to interface.rs
+
+    let undone = history::undo(count)?;
+    if undone.is_empty() {
+        writeln!(out,"Nothing to undo")?;
+    }
+
+    let mut reverted : Vec<String> = Vec::new();
+    for change in undone {
+        if change.secret {
+            writeln!(out,"{} {} {}, secret values can't be reverted",
+                theme::warning("Skipped"),
+                change.scope,
+                theme::key(&change.key)
+            )?;
+            continue;
+        }
+
+        writeln!(out,"Reverted {} {} to {}",
+            change.scope,
+            theme::key(&change.key),
+            theme::key_value_set(display_option(&change.old))
+        )?;
+        reverted.push(change.key);
+    }
+
+    Ok(reverted)
+}
+
+fn process_profile<W : Write>(out : &mut W, matches : &clap::ArgMatches, selected : Option<&str>) -> Result<(),Error> {
+    //! the profile subcommands, internal to interface.rs
+
+    match matches.subcommand() {
+        ("create", Some(create_matches)) => {
+            let name = create_matches.value_of("NAME").unwrap_or("");
+            profile::create(name)?;
+            writeln!(out,"Created profile {}",theme::key(name))?;
+        },
+        ("delete", Some(delete_matches)) => {
+            let name = delete_matches.value_of("NAME").unwrap_or("");
+            profile::delete(name)?;
+            writeln!(out,"Deleted profile {}",theme::key(name))?;
+        },
+        ("use", Some(use_matches)) => {
+            let name = use_matches.value_of("NAME");
+            profile::use_profile(name)?;
+            match name {
+                Some(name) => writeln!(out,"Using profile {}",theme::key(name))?,
+                None => writeln!(out,"Not using a profile")?,
+            }
+        },
+        _ => {
+            let active = match selected {
+                Some(selected) => Some(selected.to_string()),
+                None => profile::get_active()?,
             };
-            
-            println!("{}{} {} to {} {}",
-                location.to_string_cap(),
-                if location == Location::Best { "Set" } else { " set" },
-                theme::key(key),
-                theme::key_value_set(value),
-                old
-            );
+            for name in profile::list()? {
+                if active.as_ref() == Some(&name) {
+                    writeln!(out,"{} {}",theme::key_value_set(&name),theme::comment("(active)"))?;
+                } else {
+                    writeln!(out,"{}",theme::key(&name))?;
+                }
+            }
         },
-        Err(error) => {
-            println!("Error setting \'{}\': {}",
-                theme::key(key),
-                theme::error(error.to_string()),
-            ); 
-        }
-    } 
+    }
+
+    Ok(())
+}
+
+fn display_stored(key : &str, value : Type) -> String {
+    //! a value as it was stored, which may still be encrypted
+
+    if crypt::is_encrypted(&value) { "[encrypted]".to_string() }
+    else { format!("{}",secrets::mask(key,value)) }
 }
 
+fn display_option(value : &Option<Type>) -> String {
+    match value {
+        Some(ref value) if crypt::is_encrypted(value) => "[encrypted]".to_string(),
+        Some(value) => format!("{}",value),
+        None => "[NONE]".to_string(),
+    }
+}
 
 pub fn app() -> clap::App<'static,'static> {
     //! [CLAP.RS](https://clap.rs/) app for easy integration.
@@ -135,7 +646,11 @@ pub fn app() -> clap::App<'static,'static> {
     //!
     //! Using ***lpsettings*** by itself.
     //!
-    //! ```rust
+    //! ```rust,no_run
+    //! # extern crate clap;
+    //! # extern crate lpsettings;
+    //! # use lpsettings::interface;
+    //! # fn main() {
     //! let app = interface::app()
     //!     .get_matches();
     //!
@@ -143,12 +658,17 @@ pub fn app() -> clap::App<'static,'static> {
     //!     Err(error) => { println!("{}",error); }
     //!     Ok(_) => { }
     //! }
+    //! # }
     //! ```
     //!
     //! Using ***lpsettings*** as part of another app.
     //!
-    //! ```rust
-    //! let app = clap::App("newapp")
+    //! ```rust,no_run
+    //! # extern crate clap;
+    //! # extern crate lpsettings;
+    //! # use lpsettings::interface;
+    //! # fn main() {
+    //! let app = clap::App::new("newapp")
     //!     .subcommand(interface::app().name("settings"))
     //!     .get_matches();
     //!
@@ -156,6 +676,7 @@ pub fn app() -> clap::App<'static,'static> {
     //!     ("settings", Some(matches)) => { interface::process(matches); },
     //!     _ => {},
     //! }
+    //! # }
     //! ```
 
     clap::App::new("lpsettings")
@@ -172,14 +693,135 @@ pub fn app() -> clap::App<'static,'static> {
             .arg(clap::Arg::with_name("desc")
                 .short("d")
                 .long("desc")
-                .help("Includes descriptions")))
+                .help("Includes descriptions"))
+            .arg(clap::Arg::with_name("yes")
+                .short("y")
+                .long("yes")
+                .help("Doesn't ask, uses the defaults for anything not answered"))
+            .arg(clap::Arg::with_name("from")
+                .long("from")
+                .value_name("FILE")
+                .help("Takes the answers from a settings file"))
+            .arg(clap::Arg::with_name("set")
+                .long("set")
+                .value_name("KEY=VALUE")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Answers a single key, can be used more than once"))
+            .arg(clap::Arg::with_name("local")
+                .long("local")
+                .help("Creates the local settings file for the project, in this folder if it isn't in one yet"))
+            .arg(clap::Arg::with_name("force")
+                .long("force")
+                .help("Initializes the local settings file even if it already exists")))
         
         .subcommand(clap::SubCommand::with_name("list")
             .about("List all current settings")
+            .arg(clap::Arg::with_name("FILTER")
+                .help("Only list keys under this prefix, or matching a pattern like `lprun.*.freq`"))
             .arg(clap::Arg::with_name("possible")
                 .short("p")
                 .long("possible")
-                .help("Lists all valid options")))
+                .help("Lists all valid options"))
+            .arg(clap::Arg::with_name("effective")
+                .short("e")
+                .long("effective")
+                .help("Shows the value used for each key and where it comes from")
+                .conflicts_with("possible"))
+            .arg(clap::Arg::with_name("tree")
+                .long("tree")
+                .help("Shows nested tables as an indented tree"))
+            .arg(clap::Arg::with_name("with-desc")
+                .long("with-desc")
+                .help("Shows each key's description next to its value")))
+
+        .subcommand(clap::SubCommand::with_name("diff")
+            .about("Shows the keys added, removed and changed between the global and local settings, the settings and a file, or a backup and now")
+            .arg(clap::Arg::with_name("FILE")
+                .help("Settings file to compare the current settings to"))
+            .arg(clap::Arg::with_name("backup")
+                .long("backup")
+                .value_name("NAME")
+                .help("Backup to compare to now, `latest` for the newest one")
+                .conflicts_with("FILE")))
+
+        .subcommand(clap::SubCommand::with_name("backup")
+            .about("Saves a copy of the settings file to compare to later")
+            .arg(clap::Arg::with_name("list")
+                .long("list")
+                .help("Lists the backups")))
+
+        .subcommand(clap::SubCommand::with_name("edit")
+            .about("Opens the settings file in $VISUAL or $EDITOR, checking it before it is saved"))
+
+        .subcommand(clap::SubCommand::with_name("theme")
+            .about("Shows the colors of the theme, set with the lpsettings.theme.* settings")
+            .arg(clap::Arg::with_name("PRESET")
+                .possible_values(&theme::PRESETS)
+                .help("Shows one of the built in themes instead")))
+
+        .subcommand(clap::SubCommand::with_name("paths")
+            .visible_alias("info")
+            .about("Shows where the settings files are, if they can be read and written, and the versions in use"))
+
+        .subcommand(clap::SubCommand::with_name("tui")
+            .about("Opens a full screen editor to browse and change the settings"))
+
+        .subcommand(clap::SubCommand::with_name("completions")
+            .about("Writes a shell completion script to stdout")
+            .arg(clap::Arg::with_name("SHELL")
+                .help("The shell to write the script for")
+                .possible_values(&completion::SHELLS)
+                .required(true)))
+
+        // used by the completion scripts, not meant to be used directly
+        .subcommand(clap::SubCommand::with_name("complete")
+            .setting(clap::AppSettings::Hidden)
+            .subcommand(clap::SubCommand::with_name("keys"))
+            .subcommand(clap::SubCommand::with_name("values")
+                .arg(clap::Arg::with_name("KEY")
+                    .required(true))))
+
+        .subcommand(clap::SubCommand::with_name("unset")
+            .about("Removes a setting")
+            .arg(clap::Arg::with_name("KEY")
+                .help("Setting's address / path")
+                .required(true)))
+
+        .subcommand(clap::SubCommand::with_name("import")
+            .about("Imports all the settings from another settings file")
+            .arg(clap::Arg::with_name("FILE")
+                .help("The settings file to import")
+                .required(true)))
+
+        .subcommand(clap::SubCommand::with_name("profile")
+            .about("Manages named profiles that overlay the settings")
+            .subcommand(clap::SubCommand::with_name("list")
+                .about("Lists all the profiles"))
+            .subcommand(clap::SubCommand::with_name("create")
+                .about("Creates a new empty profile")
+                .arg(clap::Arg::with_name("NAME")
+                    .help("Name of the profile")
+                    .required(true)))
+            .subcommand(clap::SubCommand::with_name("delete")
+                .about("Deletes a profile")
+                .arg(clap::Arg::with_name("NAME")
+                    .help("Name of the profile")
+                    .required(true)))
+            .subcommand(clap::SubCommand::with_name("use")
+                .about("Sets the profile to use when none is given, leave empty to stop using one")
+                .arg(clap::Arg::with_name("NAME")
+                    .help("Name of the profile"))))
+
+        .subcommand(clap::SubCommand::with_name("history")
+            .about("Shows the history of changes made to the settings")
+            .arg(clap::Arg::with_name("KEY")
+                .help("Only show changes to this setting")))
+
+        .subcommand(clap::SubCommand::with_name("undo")
+            .about("Reverts the last changes made to the settings")
+            .arg(clap::Arg::with_name("COUNT")
+                .help("Number of commands to revert; defaults to 1")))
 
     // switches
         .arg(clap::Arg::with_name("local")
@@ -191,6 +833,44 @@ pub fn app() -> clap::App<'static,'static> {
             .help("Apply action to global settings file; default")
             .conflicts_with("local"))
 
+        .arg(clap::Arg::with_name("system")
+            .long("system")
+            .help("Apply action to the system-wide settings file, needed to change it")
+            .conflicts_with_all(&["local", "global"]))
+
+        .arg(clap::Arg::with_name("format")
+            .long("format")
+            .value_name("FORMAT")
+            .possible_values(&output::FORMATS)
+            .global(true)
+            .help("How values are written out, `raw` is only the value"))
+
+        .arg(clap::Arg::with_name("color")
+            .long("color")
+            .value_name("WHEN")
+            .possible_values(&theme::COLOR_CHOICES)
+            .global(true)
+            .help("When to use colors, `auto` only uses them in a terminal"))
+
+        .arg(clap::Arg::with_name("profile")
+            .long("profile")
+            .value_name("NAME")
+            .help("Uses the profile on top of the settings, values set are written into the profile"))
+
+        .arg(clap::Arg::with_name("encrypt")
+            .long("encrypt")
+            .help("Encrypts the value with the local key file before saving it")
+            .requires("VALUE"))
+
+        .arg(clap::Arg::with_name("no-expand")
+            .long("no-expand")
+            .help("Shows the value without expanding references to other settings")
+            .conflicts_with("VALUE"))
+
+        .arg(clap::Arg::with_name("reveal")
+            .long("reveal")
+            .help("Shows the value of secret settings instead of masking it"))
+
     // parameters
         .arg(clap::Arg::with_name("KEY")
             .help("Setting's address / path")
@@ -0,0 +1,128 @@
+//! expanding references inside of text values.
+//!
+//! a text value can reference other settings and environmental
+//! variables, which are expanded when the value is read.
+//!
+//! ```text
+//! lprun.repo.file = "${env:HOME}/.cache/love-links.toml"
+//! project.build = "${user.name}-build"
+//! literal = "costs $${PRICE}"   # read as "costs ${PRICE}"
+//! ```
+//!
+//! a `${` without a closing `}` is kept as it is. any other `${...}`
+//! that was meant literally, in a value written before references
+//! were expanded, has to be escaped as `$${...}` or it is an error
+//! when read.
+
+use std::env;
+use std::collections::HashMap;
+
+use failure::Error;
+
+use error::ErrorKind;
+
+use Type;
+
+pub fn expand<F>(key : &str, value : Type, lookup : &F) -> Result<Type,Error>
+    where F : Fn(&str) -> Result<Option<Type>,Error>
+{
+    //! expands all the references in the value of `key`, using
+    //! `lookup` to get the raw value of referenced keys.
+    //!
+    //! errors if a reference can't be found or if the references
+    //! loop back onto themselves.
+
+    let mut stack : Vec<String> = vec![key.to_string()];
+    expand_value(value,lookup,&mut stack)
+}
+
+fn expand_value<F>(value : Type, lookup : &F, stack : &mut Vec<String>) -> Result<Type,Error>
+    where F : Fn(&str) -> Result<Option<Type>,Error>
+{
+    match value {
+        Type::Text(text) => Ok(Type::Text(expand_text(&text,lookup,stack)?)),
+        Type::Array(array) => {
+            let mut expanded : Vec<Type> = Vec::new();
+            for value in array {
+                expanded.push(expand_value(value,lookup,stack)?);
+            }
+            Ok(Type::Array(expanded))
+        },
+        Type::Complex(hash) => {
+            let mut expanded = HashMap::new();
+            for (key,value) in hash {
+                expanded.insert(key,expand_value(value,lookup,stack)?);
+            }
+            Ok(Type::Complex(expanded))
+        },
+        value => Ok(value),
+    }
+}
+
+fn expand_text<F>(text : &str, lookup : &F, stack : &mut Vec<String>) -> Result<String,Error>
+    where F : Fn(&str) -> Result<Option<Type>,Error>
+{
+    let mut expanded = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let part = &rest[start..];
+
+        if let Some(escaped) = part.strip_prefix("$${") {
+            // escaped, so the reference is kept as is
+            expanded.push_str("${");
+            rest = escaped;
+        } else if part.starts_with("${") {
+            match part.find('}') {
+                Some(end) => {
+                    expanded.push_str(&resolve(&part[2..end],lookup,stack)?);
+                    rest = &part[end+1..];
+                },
+                None => {
+                    // not a reference, values written before references
+                    // were expanded can have a lone `${`
+                    expanded.push_str(part);
+                    rest = "";
+                },
+            }
+        } else {
+            expanded.push('$');
+            rest = &part[1..];
+        }
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+fn resolve<F>(reference : &str, lookup : &F, stack : &mut Vec<String>) -> Result<String,Error>
+    where F : Fn(&str) -> Result<Option<Type>,Error>
+{
+    //! gets the text that should replace the reference
+
+    if let Some(name) = reference.strip_prefix("env:") {
+        return env::var(name)
+            .map_err(|_| ErrorKind::KeyNotFound(format!("env:{}",name)).into());
+    }
+
+    if stack.iter().any(|key| key == reference) {
+        return Err(ErrorKind::InvalidInput(format!("Settings reference each other: {} -> {}",stack.join(" -> "),reference)).into());
+    }
+
+    let value = match lookup(reference)? {
+        Some(value) => value,
+        None => return Err(Error::from(ErrorKind::KeyNotFound(reference.to_string()))
+            .context(format!("Cannot expand '{}', use '$${{' for a literal '${{'",stack[stack.len()-1]))
+            .into()),
+    };
+
+    stack.push(reference.to_string());
+    let value = expand_value(value,lookup,stack)?;
+    stack.pop();
+
+    match value {
+        Type::Complex(_) | Type::Array(_) => Err(ErrorKind::InvalidInput(format!("'{}' is not a single value and can't be referenced",reference)).into()),
+        value => Ok(format!("{}",value)),
+    }
+}
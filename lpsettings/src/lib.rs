@@ -1,59 +1,143 @@
-/// LPSETTINGS
-/// 
-/// a library and a binary
-/// the library contains functions to read and write to
-/// a local configuration (in working directory or global directory)
-/// for all the lovepack applications. you should not be using this
-/// unless you are writing something part of the lovepack family.
-/// 
-/// the binary just uses the interface(.rs) and creates a terminal
-/// application where you can read and write settings
+//! LPSETTINGS
+//! 
+//! a library and a binary
+//! the library contains functions to read and write to
+//! a local configuration (in working directory or global directory)
+//! for all the lovepack applications. you should not be using this
+//! unless you are writing something part of the lovepack family.
+//! 
+//! the binary just uses the interface(.rs) and creates a terminal
+//! application where you can read and write settings
 
 #[macro_use] extern crate failure;
 use failure::Error;
 extern crate toml;
 extern crate serde;
+extern crate serde_json;
 
 // terminal stuff
 extern crate ansi_term;
 
 #[cfg(feature = "cli")]
 extern crate clap;
+#[cfg(feature = "cli")]
+extern crate console;
 
 // for update helpers
 extern crate chrono;
 
+// for encrypted values
+extern crate chacha20poly1305;
+extern crate base64;
+extern crate dirs;
+
 // settings stuff
 extern crate settingsfile;
-use settingsfile::{ SupportedType, ShadowSettings };
+use settingsfile::{ SupportedType, ShadowSettings, Settings };
 pub use settingsfile::Type as Type;
 
 #[macro_use] extern crate serde_derive;
 #[macro_use] extern crate log;
 extern crate version_lp;
 
-use std::path::PathBuf;
+use std::path::{ Path, PathBuf };
+pub use scope::Scope;
+use output::OutputFormat;
+use std::fs::File;
+use std::io::{ self, Write };
 
 #[cfg(feature = "cli")]
 pub mod interface;
+#[cfg(feature = "cli")]
+pub mod completion;
+#[cfg(feature = "cli")]
+mod tui;
+#[cfg(feature = "cli")]
+mod edit;
 
 mod settings;
-mod location;
-mod theme;
+
 mod map;
+mod store;
+mod interpolate;
+mod environment;
+mod value;
+pub mod secrets;
+pub mod crypt;
+pub mod profile;
 pub mod update;
-
+pub mod history;
+pub mod output;
+pub mod list;
+pub mod diff;
+pub mod backup;
+pub mod init;
+pub mod prompt;
+pub mod theme;
+pub mod scope;
+pub mod system;
+pub mod project;
+pub mod info;
+pub mod error;
 
 pub fn get_folder() -> PathBuf {
-    PathBuf::from(settingsfile::Format::get_path(&settings::Configuration{}))
+    PathBuf::from(settingsfile::Format::get_path(&settings::Configuration::new()))
+}
+
+pub fn project_root() -> Option<PathBuf> {
+    //! the root of the project the current directory is in, the
+    //! closest folder above it with a local settings file. `None`
+    //! outside of a project, where the local settings are in the
+    //! current directory.
+
+    project::find_root()
+}
+
+fn get_config(secret : bool) -> settings::Configuration {
+    if secret { settings::Configuration::secrets() }
+    else { settings::Configuration::new() }
+}
+
+fn get_file_global(secret : bool) -> String {
+    settingsfile::Format::get_path_and_file(&get_config(secret))
+}
+
+fn get_file_local(secret : bool) -> String {
+    settingsfile::Format::get_local_path_and_filename(&get_config(secret))
+}
+
+fn load_settings(secret : bool) -> Result<ShadowSettings<settings::Configuration>,Error> {
+    //! loads either the settings or the secrets store
+
+    if secret { return secrets::load(); }
+
+    // loaded one at a time, `load` rewraps the errors and the kind is lost
+    let mut settings = ShadowSettings::new(settings::Configuration::new());
+    settings.load_global()?;
+    settings.load_local()?;
+    Ok(settings)
+}
+
+fn save_settings(settings : &ShadowSettings<settings::Configuration>, secret : bool) -> Result<(),Error> {
+    if secret { secrets::save(settings) }
+    else { settings.save() }
 }
 
 pub fn get_value(key : &str) -> Result<Option<Type>,Error> {
     //! Get the value.
+    //! 
+    //! Encrypted values are decrypted using the local key file, and
+    //! references to other settings (`${user.name}`) or environmental
+    //! variables (`${env:HOME}`) are expanded.
 
-    let mut settings = ShadowSettings::new(settings::Configuration{});
-    settings.load()?;
-    Ok(settings.get_value(key))
+    store::Store::load()?.get_value(key,&Scope::Best)
+}
+
+pub fn get_value_raw(key : &str) -> Result<Option<Type>,Error> {
+    //! Get the value without expanding any references, useful when
+    //! the value is going to be edited.
+
+    store::Store::load()?.get_value_raw(key,&Scope::Best)
 }
 
 pub fn get_value_or<A>(key : &str, default_value : &A) -> Type 
@@ -77,9 +161,7 @@ pub fn get_value_local(key : &str) -> Result<Option<Type>,Error> {
     //! Can return empty if the local file doesn't have a value but
     //! the global one does.
     
-    let mut settings = ShadowSettings::new(settings::Configuration{});
-    settings.load()?;
-    Ok(settings.get_value_local(key))
+    store::Store::load()?.get_value(key,&Scope::Local)
 }
 
 pub fn get_value_global(key : &str) -> Result<Option<Type>,Error> {
@@ -88,177 +170,257 @@ pub fn get_value_global(key : &str) -> Result<Option<Type>,Error> {
     //! Can return empty if the global file doesn't have a value but
     //! the local one does.
     
-    let mut settings = ShadowSettings::new(settings::Configuration{});
-    settings.load()?;
-    Ok(settings.get_value_global(key))
+    store::Store::load()?.get_value(key,&Scope::Global)
+}
+
+pub fn get_value_in(key : &str, scope : Scope) -> Result<Option<Type>,Error> {
+    //! Get the value from the given scope, `Best` looks in the local
+    //! configuration, then the global one and then the system one.
+
+    store::Store::load()?.get_value(key,&scope)
+}
+
+pub fn get_value_raw_in(key : &str, scope : Scope) -> Result<Option<Type>,Error> {
+    //! Get the value from the given scope without expanding any
+    //! references.
+
+    store::Store::load()?.get_value_raw(key,&scope)
 }
 
 pub fn set_value<A>(key : &str, value : &A) -> Result<Option<Type>,Error> 
     where A : SupportedType
 {
     //! Sets the value on the global level.
+    //! 
+    //! Keys marked as secret are written to the secrets file. The old
+    //! value is returned as it was stored, an encrypted value isn't
+    //! decrypted (see `crypt::decrypt`).
 
-    let mut settings = ShadowSettings::new(settings::Configuration{});
-    settings.load()?;
+    let secret = secrets::is_secret(key);
+    let mut settings = load_settings(secret)?;
     let old_value = settings.get_value(key);
+    let old_global = settings.get_value_global(key);
     settings.set_value_global(key,value)?;
-    settings.save()?;
+    save_settings(&settings,secret)?;
+
+    history::record(&[history::Change::new("set","global",&get_file_global(secret),
+        key,old_global,Some(value.wrap()))]);
 
     Ok(old_value)
 }
 
+pub fn set_value_encrypted<A>(key : &str, value : &A) -> Result<Option<Type>,Error> 
+    where A : SupportedType
+{
+    //! Sets the value on the global level, encrypted with the local
+    //! key file. The key file is created if it doesn't exist yet.
+
+    set_value(key,&crypt::encrypt(&value.wrap())?)
+}
+
 pub fn set_value_local<A>(key : &str, value : &A) -> Result<Option<Type>,Error> 
     where A : SupportedType
 {
     //! Sets the value on the local level.
+    //! 
+    //! Keys marked as secret are written to the secrets file. The old
+    //! value is returned as it was stored, like `set_value`.
 
-    let mut settings = ShadowSettings::new(settings::Configuration{});
-    settings.load()?;
+    let secret = secrets::is_secret(key);
+    let mut settings = load_settings(secret)?;
     let old_value = settings.get_value_local(key);
     settings.set_value_local(key,value)?;
-    settings.save()?;
+    save_settings(&settings,secret)?;
+
+    history::record(&[history::Change::new("set","local",&get_file_local(secret),
+        key,old_value.clone(),Some(value.wrap()))]);
 
     Ok(old_value)
 }
 
-pub fn initalize(desc : bool) -> Result<(),Error>{
-    //! initalizes the global settings (only global)
-    //! 
-    //! uses the map.toml file from source to determine what
-    //! should be initalized and gives descriptions.
-    //! 
-    //! if the user leaves the entry blank it will not be editied or 
-    //! created, so the existing values will stay the same or new values
-    //! will not be created.
-    
-    let map =  map::create_options_map()?;
-    let lib_version = version_lp::Version::from_str(env!("CARGO_PKG_VERSION")).unwrap();
+pub fn set_value_local_encrypted<A>(key : &str, value : &A) -> Result<Option<Type>,Error> 
+    where A : SupportedType
+{
+    //! Sets the value on the local level, encrypted with the local
+    //! key file. The key file is created if it doesn't exist yet.
 
-    let mut settings = ShadowSettings::new(settings::Configuration{});
-    settings.load()?;
+    set_value_local(key,&crypt::encrypt(&value.wrap())?)
+}
 
-    println!("Initializing settings, leave empty to keep existing / not set.");
+pub fn set_value_in<A>(key : &str, value : &A, scope : Scope) -> Result<Option<Type>,Error> 
+    where A : SupportedType
+{
+    //! Sets the value in the given scope, `Best` is the global level.
+    //! Secret keys can't be set in the `System` scope.
 
-    for m in map {
-        if let Some(true) = m.init {
-            
-            if let Some(added) = m.added {
-                // if the added version is greater than the current version
-                // then this isn't valid
-                if added > lib_version { continue; }
-            }
-            if let Some(removed) = m.removed {
-                // if the removed version is less or equal than the current version
-                // then it isn't valid
-                if removed <= lib_version { continue; }
-            }
+    match scope {
+        Scope::Local => set_value_local(key,value),
+        Scope::System => system::set_value("set",key,&value.wrap()),
+        _ => set_value(key,value),
+    }
+}
 
-            let old_value = settings.get_value_global(&m.key);
-            
-            let mut question = if let Some(old_value) = old_value {
-                format!("{}({})",
-                    theme::key(&m.key),
-                    theme::key_value(format!("{}",old_value)))
-            } else {
-                format!("{}",
-                    theme::key(&m.key))
-            };
+pub fn unset_value(key : &str) -> Result<Option<Type>,Error> {
+    //! Removes the value on the global level, returns the
+    //! value that was removed as it was stored.
 
-            // adds the descriptions if requested.
-            if desc {
-                question = format!("{} - {}",
-                    question,
-                    theme::comment(&m.desc));
-            }
+    let secret = secrets::is_secret(key);
+    let mut settings = load_settings(secret)?;
+    let old_value = settings.delete_key_global(key);
+    save_settings(&settings,secret)?;
 
-            let new_value = map::get_user_input(&question);
-            if new_value.len() > 0 {
-                settings.set_value_global(&m.key,&new_value)?;
-            }
-        }
+    if old_value.is_some() {
+        history::record(&[history::Change::new("unset","global",&get_file_global(secret),
+            key,old_value.clone(),None)]);
     }
 
-    // saves the new settings
-    settings.save()?;
-
-    Ok(())
+    Ok(old_value)
 }
 
-pub fn list_possible() -> Result<(),Error> {
-    let map =  map::create_options_map()?;
-    let lib_version = version_lp::Version::from_str(env!("CARGO_PKG_VERSION")).unwrap();
-
-    for m in map {
-        if let Some(added) = m.added {
-            // if the added version is greater than the current version
-            // then this isn't valid
-            if added > lib_version { continue; }
-        }
-        if let Some(removed) = m.removed {
-            // if the removed version is less or equal than the current version
-            // then it isn't valid
-            if removed <= lib_version { continue; }
-        }
+pub fn unset_value_local(key : &str) -> Result<Option<Type>,Error> {
+    //! Removes the value on the local level, returns the
+    //! value that was removed as it was stored.
+
+    let secret = secrets::is_secret(key);
+    let mut settings = load_settings(secret)?;
+    let old_value = settings.delete_key_local(key);
+    save_settings(&settings,secret)?;
 
-        println!("{} - {}",
-            theme::key(&m.key),
-            theme::comment(&m.desc)
-        );
+    if old_value.is_some() {
+        history::record(&[history::Change::new("unset","local",&get_file_local(secret),
+            key,old_value.clone(),None)]);
     }
 
-    Ok(())
+    Ok(old_value)
 }
 
-pub fn list_current() -> Result<(),Error> {
-    let mut settings = ShadowSettings::new(settings::Configuration{});
-    settings.load()?;  
-
-    let local_keys = { 
-        let mut keys = settings.keys_local();
-        keys.sort();
-        keys 
-    };
-
-    // only put the heading if there is local settings too.
-    if local_keys.len() > 0 {
-        println!("{}",theme::heading("Global Settings"));
+pub fn unset_value_in(key : &str, scope : Scope) -> Result<Option<Type>,Error> {
+    //! Removes the value in the given scope, `Best` is the global
+    //! level. Returns the value that was removed.
+
+    match scope {
+        Scope::Local => unset_value_local(key),
+        Scope::System => system::unset_value(key),
+        _ => unset_value(key),
     }
+}
 
-    let global_keys = { 
-        let mut keys = settings.keys_global();
-        keys.sort();
-        keys 
-    };
-
-    for k in global_keys {
-        if let Some(value) = settings.get_value_global(&k) {
-            println!("{}: {}",
-                theme::key(k),
-                theme::key_value(format!("{}",value))
-            );
-        } else {
-            println!("{}",
-                theme::key(k)
-            );
-        }
+pub fn import<P : AsRef<Path>>(path : P) -> Result<Vec<String>,Error> {
+    //! Imports all the values from another settings file into the 
+    //! global level. Returns the keys that were imported.
+
+    import_into(path.as_ref(),false)
+}
+
+pub fn import_local<P : AsRef<Path>>(path : P) -> Result<Vec<String>,Error> {
+    //! Imports all the values from another settings file into the 
+    //! local level. Returns the keys that were imported.
+
+    import_into(path.as_ref(),true)
+}
+
+pub fn import_in<P : AsRef<Path>>(path : P, scope : Scope) -> Result<Vec<String>,Error> {
+    //! Imports all the values from another settings file into the 
+    //! given scope, `Best` is the global level.
+
+    match scope {
+        Scope::System => system::import(path.as_ref()),
+        _ => import_into(path.as_ref(),scope.is_local()),
     }
+}
+
+fn import_into(path : &Path, local : bool) -> Result<Vec<String>,Error> {
+    let file = File::open(path)?;
+    let imported = Settings::create_from(&file,settings::Configuration::new())?;
+
+    let mut settings = load_settings(false)?;
+    let mut secret_store = load_settings(true)?;
+    let mut imported_secret = false;
 
-    if local_keys.len() > 0 {
-        println!("{}",theme::heading("Local Settings"));
-
-        for k in local_keys {
-            if let Some(value) = settings.get_value_local(&k) {
-                println!("{}: {}",
-                    theme::key(k),
-                    theme::key_value(format!("{}",value))
-                );
-            } else {
-                println!("{}",
-                    theme::key(k)
-                );
+    let scope = if local { "local" } else { "global" };
+
+    let mut keys = imported.keys();
+    keys.sort();
+
+    let mut changes : Vec<history::Change> = Vec::new();
+    for key in keys.iter() {
+        if let Some(value) = imported.get_value(key) {
+            let secret = secrets::is_secret(key);
+            imported_secret = imported_secret || secret;
+            let store = if secret { &mut secret_store } else { &mut settings };
+
+            let old_value = if local { 
+                store.get_value_local(key) 
+            } else { 
+                store.get_value_global(key) 
+            };
+
+            if local { 
+                store.set_value_local(key,&value)?; 
+            } else { 
+                store.set_value_global(key,&value)?; 
             }
+
+            let settings_file = if local { get_file_local(secret) } else { get_file_global(secret) };
+            changes.push(history::Change::new("import",scope,&settings_file,
+                key,old_value,Some(value)));
         }
     }
 
+    save_settings(&settings,false)?;
+    if imported_secret {
+        save_settings(&secret_store,true)?;
+    }
+    history::record(&changes);
+
+    Ok(keys)
+}
+
+pub fn initalize(desc : bool) -> Result<(),Error>{
+    //! initalizes the global settings (only global)
+    //! 
+    //! uses the map.toml file from source to determine what
+    //! should be initalized and gives descriptions.
+    //! 
+    //! if the user leaves the entry blank it will not be editied or 
+    //! created, so the existing values will stay the same or new values
+    //! will not be created. see `init::run` for initializing without
+    //! asking.
+    
+    init::run(&init::Options::new().desc(desc),&mut prompt::Terminal::new())?;
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn list_possible() -> Result<(),Error> {
+    list_possible_as(OutputFormat::Text)
+}
+
+pub fn list_possible_as(format : OutputFormat) -> Result<(),Error> {
+    //! prints all the valid options in the given format
+
+    list_possible_to(&mut io::stdout(),format)
+}
+
+pub fn list_possible_to<W : Write>(out : &mut W, format : OutputFormat) -> Result<(),Error> {
+    //! writes all the valid options in the given format to `out`
+
+    list::possible(out,&list::Options::new(format))
+}
+
+pub fn list_current() -> Result<(),Error> {
+    list_current_as(OutputFormat::Text)
+}
+
+pub fn list_current_as(format : OutputFormat) -> Result<(),Error> {
+    //! prints all the current settings in the given format, values 
+    //! of secret keys are masked.
+
+    list_current_to(&mut io::stdout(),format)
+}
+
+pub fn list_current_to<W : Write>(out : &mut W, format : OutputFormat) -> Result<(),Error> {
+    //! writes all the current settings in the given format to `out`,
+    //! values of secret keys are masked.
+
+    list::current(out,&list::Options::new(format))
+}
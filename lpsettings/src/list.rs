@@ -0,0 +1,453 @@
+//! listing the current settings and the possible options.
+//!
+//! the listings can be filtered by a key prefix or a glob pattern,
+//! where `*` matches any part of a single key segment and `?` a
+//! single character, so `lprun.*.freq` matches `lprun.update.freq`.
+//! a whole `**` segment matches any number of segments, so
+//! `**.freq` matches `lprun.repo.update.freq`. a key is listed if the
+//! pattern matches it or any table it is in.
+
+use std::io::Write;
+
+use failure::Error;
+
+use output;
+use output::OutputFormat;
+use map;
+use map::OptionsMap;
+use theme;
+use secrets;
+use crypt;
+use load_settings;
+use store::{ Store, Source };
+use environment;
+use profile;
+use scope::Scope;
+use system;
+use project;
+use get_file_local;
+use Type;
+
+/// a key, its value and a note shown after the value
+type Row = (String,Option<Type>,String);
+
+/// how the settings should be listed
+#[derive(Clone,Default)]
+pub struct Options {
+    pub format : OutputFormat,
+    /// a key prefix or glob pattern, only matching keys are listed
+    pub filter : Option<String>,
+    /// show the nested tables as an indented tree, only for `Text`
+    pub tree : bool,
+    /// show the description from the options map next to each key
+    pub with_desc : bool,
+    /// show the value that is used for each key and where it comes
+    /// from, instead of each file separately
+    pub effective : bool,
+    /// only list the settings in this scope, `Best` lists both
+    pub scope : Scope,
+    /// the profile to use instead of the one from the environment or
+    /// the settings
+    pub profile : Option<String>,
+}
+
+impl Options {
+    pub fn new(format : OutputFormat) -> Options {
+        Options { format, .. Options::default() }
+    }
+
+    pub fn filter(mut self, filter : &str) -> Options {
+        self.filter = Some(filter.to_string());
+        self
+    }
+
+    pub fn tree(mut self, tree : bool) -> Options {
+        self.tree = tree;
+        self
+    }
+
+    pub fn with_desc(mut self, with_desc : bool) -> Options {
+        self.with_desc = with_desc;
+        self
+    }
+
+    pub fn effective(mut self, effective : bool) -> Options {
+        self.effective = effective;
+        self
+    }
+
+    pub fn scope(mut self, scope : Scope) -> Options {
+        self.scope = scope;
+        self
+    }
+
+    pub fn profile(mut self, profile : Option<&str>) -> Options {
+        self.profile = profile.map(|profile| profile.to_string());
+        self
+    }
+
+    fn includes(&self, key : &str) -> bool {
+        match self.filter {
+            None => true,
+            Some(ref filter) => matches(filter,key),
+        }
+    }
+}
+
+pub fn possible<W : Write>(out : &mut W, options : &Options) -> Result<(),Error> {
+    //! writes all the valid options
+
+    let map =  map::create_options_map()?;
+    let mut possible : Vec<output::Possible> = Vec::new();
+
+    for m in map {
+        if !map::is_current(&m) { continue; }
+        if !options.includes(&m.key) { continue; }
+
+        if options.format != OutputFormat::Text {
+            possible.push(output::Possible { key : m.key, desc : m.desc });
+            continue;
+        }
+
+        writeln!(out,"{} - {}",
+            theme::key(&m.key),
+            theme::comment(&m.desc)
+        )?;
+    }
+
+    if options.format != OutputFormat::Text {
+        output::write_possible(out,options.format,&possible)?;
+    }
+
+    Ok(())
+}
+
+pub fn current<W : Write>(out : &mut W, options : &Options) -> Result<(),Error> {
+    //! writes all the current settings, values of secret keys are masked
+    //! and references are expanded like `get` does. with `Best` the
+    //! active profile's values are listed above the others.
+
+    if options.effective { return effective(out,options); }
+
+    let store = Store::load()?.profile(options.profile.as_deref());
+    let map = if options.with_desc { map::create_options_map()? } else { Vec::new() };
+
+    // the values of each scope as they are stored, the local ones
+    // with the ones inherited from the projects above. the profiles
+    // aren't, the active one is listed on its own.
+    let rows = |scope : Scope| -> Vec<Row> {
+        if options.scope != Scope::Best && options.scope != scope { return Vec::new(); }
+
+        store.keys(&scope).into_iter()
+            .filter(|key| !profile::is_profile_key(key) && options.includes(key))
+            .map(|k| {
+                let value = expand(&store,&k,store.get_stored(&k,&scope));
+                (k,value,String::new())
+            }).collect()
+    };
+
+    let system = rows(Scope::System);
+    let global = rows(Scope::Global);
+    let local = rows(Scope::Local);
+
+    // the active profile's keys, without the `profiles.NAME.` part
+    let active = if options.scope == Scope::Best { store.get_profile() } else { None };
+    let profile : Vec<Row> = match active {
+        None => Vec::new(),
+        Some(ref name) => {
+            let prefix = format!("{}.",profile::get_key(name));
+            let mut keys : Vec<String> = store.keys(&Scope::Best);
+            keys.retain(|key| key.starts_with(&prefix) && options.includes(&key[prefix.len()..]));
+
+            let mut rows : Vec<Row> = Vec::new();
+            for key in keys {
+                let value = store.get_value_raw(&key,&Scope::Best)?;
+                let key = key[prefix.len()..].to_string();
+                let value = expand(&store,&key,value);
+                rows.push((key,value,String::new()));
+            }
+            rows
+        },
+    };
+
+    if options.format != OutputFormat::Text {
+        let mut entries : Vec<output::Entry> = Vec::new();
+        for (source, list) in [("system",system), ("global",global), ("local",local), ("profile",profile)].iter() {
+            for (k,value,_) in list {
+                let mut entry = output::Entry::new(k,value.clone(),source);
+                if options.with_desc {
+                    entry.desc = map::get_desc(&map,k).map(|desc| desc.to_string());
+                }
+                entries.push(entry);
+            }
+        }
+        return output::write_entries(out,options.format,&entries);
+    }
+
+    // only put the heading if there is local, system or profile settings too.
+    let headings = options.scope == Scope::Best && !(local.is_empty() && system.is_empty() && profile.is_empty());
+    if headings && !system.is_empty() {
+        writeln!(out,"{}",theme::heading("System Settings"))?;
+        display_keys(out,&system,options,&map)?;
+    }
+    if options.scope == Scope::System {
+        display_keys(out,&system,options,&map)?;
+    }
+
+    if headings {
+        writeln!(out,"{}",theme::heading("Global Settings"))?;
+    }
+
+    display_keys(out,&global,options,&map)?;
+
+    if !local.is_empty() {
+        if headings { writeln!(out,"{}",theme::heading("Local Settings"))?; }
+        display_keys(out,&local,options,&map)?;
+    }
+
+    if let Some(name) = active {
+        if !profile.is_empty() {
+            writeln!(out,"{}",theme::heading(format!("Profile Settings ({})",name)))?;
+            display_keys(out,&profile,options,&map)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn expand(store : &Store, key : &str, value : Option<Type>) -> Option<Type> {
+    //! expands the references in a listed value, masking referenced
+    //! secrets. a value that can't be expanded is listed as it is,
+    //! so the listing still shows where the broken reference is.
+
+    value.map(|value| store.expand(key,value.clone(),true).unwrap_or(value))
+}
+
+pub fn effective<W : Write>(out : &mut W, options : &Options) -> Result<(),Error> {
+    //! writes the value that is used for each key, with where it
+    //! comes from and which other places it overrides. local values
+    //! also have the file they come from, as they can be inherited
+    //! from a project above this one.
+
+    let settings = load_settings(false)?;
+    let secret_store = load_settings(true)?;
+    let system_settings = system::load()?;
+    let inherited = project::load_inherited()?;
+    let map = map::create_options_map()?;
+    let store = Store::load()?.profile(options.profile.as_deref());
+    let profile = store.get_profile()
+        .map(|profile| format!("{}.",profile::get_key(&profile)));
+
+    // every key that is set somewhere
+    let mut keys : Vec<String> = Vec::new();
+    keys.append(&mut settings.keys_global());
+    keys.append(&mut settings.keys_local());
+    keys.append(&mut secret_store.keys_global());
+    keys.append(&mut secret_store.keys_local());
+    keys.append(&mut system_settings.keys());
+    for layer in inherited.iter() {
+        keys.append(&mut layer.settings.keys());
+    }
+    keys = keys.into_iter()
+        .filter_map(|key| match profile {
+            Some(ref profile) if key.starts_with(profile) => Some(key[profile.len()..].to_string()),
+            _ if key.starts_with("profiles.") => None,
+            _ => Some(key),
+        }).collect();
+    keys.append(&mut environment::list().into_iter().map(|(key,_)| key).collect());
+    for m in map.iter() {
+        if m.default.is_some() && map::is_current(m) {
+            keys.push(m.key.to_string());
+        }
+    }
+    keys.retain(|key| options.includes(key));
+    keys.sort();
+    keys.dedup();
+
+    let mut rows : Vec<Row> = Vec::new();
+    let mut entries : Vec<output::Entry> = Vec::new();
+
+    for key in keys {
+        // each place the key is set, the first one is used
+        let mut layers : Vec<(Source,Type,Option<String>)> = Vec::new();
+        if let Some(value) = environment::get_value(&key) {
+            layers.push((Source::Env,value,None));
+        }
+        if let Some(ref profile) = profile {
+            let profile_key = format!("{}{}",profile,key);
+            if let Some(value) = secret_store.get_value(&profile_key).or_else(|| settings.get_value(&profile_key)) {
+                layers.push((Source::Profile,value,None));
+            }
+        }
+        if let Some(value) = secret_store.get_value_local(&key) {
+            layers.push((Source::Local,value,Some(get_file_local(true))));
+        } else if let Some(value) = settings.get_value_local(&key) {
+            layers.push((Source::Local,value,Some(get_file_local(false))));
+        }
+        for layer in inherited.iter() {
+            if let Some(value) = layer.settings.get_value(&key) {
+                layers.push((Source::Local,value,Some(layer.file.display().to_string())));
+            }
+        }
+        if let Some(value) = secret_store.get_value_global(&key).or_else(|| settings.get_value_global(&key)) {
+            layers.push((Source::Global,value,None));
+        }
+        if let Some(value) = system_settings.get_value(&key) {
+            layers.push((Source::System,value,None));
+        }
+        if let Some(value) = map::get_default(&map,&key) {
+            layers.push((Source::Default,value,None));
+        }
+
+        if layers.is_empty() { continue; }
+        let (source, value, file) = layers.remove(0);
+        let value = store.expand(&key,value.clone(),true).unwrap_or(value);
+        let overrides : Vec<String> = layers.iter()
+            .map(|(source,_,file)| match file {
+                Some(file) if !inherited.is_empty() => format!("{} {}",source.name(),file),
+                _ => source.name().to_string(),
+            }).collect();
+
+        if options.format != OutputFormat::Text {
+            let mut entry = output::Entry::new(&key,Some(value),source.name());
+            entry.overrides = overrides.clone();
+            entry.file = file;
+            if options.with_desc {
+                entry.desc = map::get_desc(&map,&key).map(|desc| desc.to_string());
+            }
+            entries.push(entry);
+            continue;
+        }
+
+        let mut note = match file {
+            Some(ref file) if !inherited.is_empty() => format!("[{} {}]",source.name(),file),
+            _ => format!("[{}]",source.name()),
+        };
+        if !overrides.is_empty() {
+            note = format!("{} overrides {}",note,overrides.join(", "));
+        }
+        rows.push((key,Some(value),note));
+    }
+
+    if options.format != OutputFormat::Text {
+        return output::write_entries(out,options.format,&entries);
+    }
+
+    display_keys(out,&rows,options,&map)
+}
+
+pub fn matches(pattern : &str, key : &str) -> bool {
+    //! checks if the key, or any table it is in, matches the
+    //! pattern. the pattern is matched one segment at a time.
+
+    let pattern : Vec<&str> = pattern.split('.').collect();
+    let key : Vec<&str> = key.split('.').collect();
+
+    matches_parts(&pattern,&key)
+}
+
+fn matches_parts(pattern : &[&str], key : &[&str]) -> bool {
+    match pattern.first() {
+        // whatever is left of the key is inside the matched table
+        None => true,
+        Some(&"**") => {
+            matches_parts(&pattern[1..],key)
+            || (!key.is_empty() && matches_parts(pattern,&key[1..]))
+        },
+        Some(part) => {
+            !key.is_empty()
+            && matches_segment(part.as_bytes(),key[0].as_bytes())
+            && matches_parts(&pattern[1..],&key[1..])
+        },
+    }
+}
+
+fn matches_segment(pattern : &[u8], text : &[u8]) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            matches_segment(&pattern[1..],text)
+            || (!text.is_empty() && matches_segment(pattern,&text[1..]))
+        },
+        (Some(b'?'), Some(_)) => matches_segment(&pattern[1..],&text[1..]),
+        (Some(p), Some(t)) if p == t => matches_segment(&pattern[1..],&text[1..]),
+        _ => false,
+    }
+}
+
+fn display_keys<W : Write>(out : &mut W, list : &[Row], options : &Options, map : &[OptionsMap]) -> Result<(),Error> {
+    //! writes the keys, either as they are or as a tree where each
+    //! table is only printed once and its keys are indented under it.
+
+    let mut previous : Vec<&str> = Vec::new();
+
+    for (key,value,note) in list {
+        let desc = if options.with_desc { map::get_desc(map,key) } else { None };
+
+        if !options.tree {
+            display_key(out,key,key,value,note,desc,0)?;
+            continue;
+        }
+
+        let parts : Vec<&str> = key.split('.').collect();
+        let shared = previous.iter()
+            .zip(parts.iter())
+            .take_while(|(a,b)| a == b)
+            .count()
+            .min(parts.len() - 1);
+
+        for (depth,table) in parts.iter().enumerate().take(parts.len() - 1).skip(shared) {
+            writeln!(out,"{}{}",indent(depth),theme::key(table))?;
+        }
+        display_key(out,key,parts[parts.len() - 1],value,note,desc,parts.len() - 1)?;
+
+        previous = parts;
+    }
+
+    Ok(())
+}
+
+fn display_key<W : Write>(out : &mut W, key : &str, name : &str, value : &Option<Type>, note : &str, desc : Option<&str>, depth : usize) -> Result<(),Error> {
+    //! writes a single key, `name` is what is shown for the key
+    //! and `key` is the full key used to check if it is secret.
+
+    let mut desc = match desc {
+        Some(desc) => format!(" {}",theme::comment(format!("- {}",desc))),
+        None => String::new(),
+    };
+    if !note.is_empty() {
+        desc = format!(" {}{}",theme::comment(note),desc);
+    }
+
+    match value {
+        Some(ref value) if crypt::is_encrypted(value) => {
+            writeln!(out,"{}{}: {}{}",
+                indent(depth),
+                theme::key(name),
+                theme::comment("[encrypted]"),
+                desc
+            )?;
+        },
+        Some(value) => {
+            writeln!(out,"{}{}: {}{}",
+                indent(depth),
+                theme::key(name),
+                theme::key_value(format!("{}",secrets::mask(key,value.clone()))),
+                desc
+            )?;
+        },
+        None => {
+            writeln!(out,"{}{}{}",
+                indent(depth),
+                theme::key(name),
+                desc
+            )?;
+        },
+    }
+
+    Ok(())
+}
+
+fn indent(depth : usize) -> String {
+    "  ".repeat(depth)
+}
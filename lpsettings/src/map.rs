@@ -1,7 +1,12 @@
+use std::sync::OnceLock;
+
 use version_lp::Version;
 use failure::Error;
 use toml;
 
+use profile;
+use Type;
+
 #[derive(Deserialize)]
 pub struct OptionsMap {
     pub key : String,
@@ -10,50 +15,122 @@ pub struct OptionsMap {
     pub added : Option<Version>,
     pub removed : Option<Version>,
 
+    #[allow(dead_code)]
     pub replaced_by : Option<String>,
 
     pub init : Option<bool>,
+
+    /// asked for when creating the local settings for a project
+    pub project : Option<bool>,
+
+    /// the value is stored in the secrets file and masked when shown
+    pub secret : Option<bool>,
+
+    /// the value used when the key isn't set anywhere
+    pub default : Option<Type>,
+
+    /// the only values the key can have, used for completion
+    pub choices : Option<Vec<String>>,
+
+    /// the type of the value, one of `value::TYPES`, used to check
+    /// values that are typed in
+    #[serde(rename = "type")]
+    pub kind : Option<String>,
 }
 
 #[derive(Deserialize)]
 struct Map {
+    pub version : Version,
     pub values : Vec<OptionsMap>
 }
 
-pub fn create_options_map() -> Result<Vec<OptionsMap>,Error> {
-
+fn load() -> Result<Map,Error> {
     let map_raw = include_str!("map.toml");
     let map : Map = toml::de::from_str(map_raw)?;
-    Ok(map.values)
-}
-
-pub fn get_user_input(question : &str) -> String {
-  //! shortcut function used to get user input.
-  //! 
-  //! will take the `question` and print to the screen expecting user input.
-  //!
-  //! ```rust
-  //! get_user_input("Some question for the user");
-  //! ```
-  //!
-  //! will yield
-  //!
-  //! ```bash
-  //! Some question for the user:
-  //! ```
-
-  use std::io::{stdin,stdout,Write};
-
-  let mut input = String::new();
-  {
-    let mut in_put = &mut input;
-    print!("{}: ",question);
-    let _ = stdout().flush();
-    stdin().read_line(&mut in_put).expect("");
-  }
-
-  // trims the \r\n at the end of the string.
-  input = input.replace("\r","").replace("\n","");
-  
-  input
-}
\ No newline at end of file
+    Ok(map)
+}
+
+pub fn create_options_map() -> Result<Vec<OptionsMap>,Error> {
+    Ok(load()?.values)
+}
+
+/// the map is built into the library, so it is only read once
+static MAP : OnceLock<Vec<OptionsMap>> = OnceLock::new();
+
+pub fn get_options_map() -> &'static [OptionsMap] {
+    //! the options map, read the first time it is needed. it is empty
+    //! if it can't be read, which is only logged.
+
+    MAP.get_or_init(|| match create_options_map() {
+        Ok(map) => map,
+        Err(error) => {
+            warn!("Could not read the options map: {}",error);
+            Vec::new()
+        }
+    })
+}
+
+pub fn get_version() -> Result<Version,Error> {
+    //! the version of the options map
+
+    Ok(load()?.version)
+}
+
+pub fn is_secret(map : &[OptionsMap], key : &str) -> bool {
+    //! checks if the key, or any key it is part of, is marked as secret.
+    //! keys inside of a profile are checked without the profile part.
+
+    let key = profile::strip_key(key);
+    map.iter()
+        .filter(|m| m.secret == Some(true))
+        .any(|m| key == m.key || key.starts_with(&format!("{}.",m.key)))
+}
+
+pub fn get_desc<'a>(map : &'a [OptionsMap], key : &str) -> Option<&'a str> {
+    //! the description of the key, keys inside of a profile use the
+    //! description of the key without the profile part.
+
+    let key = profile::strip_key(key);
+    map.iter()
+        .find(|m| m.key == key)
+        .map(|m| m.desc.as_str())
+}
+
+pub fn get_choices<'a>(map : &'a [OptionsMap], key : &str) -> &'a [String] {
+    //! the values the key can have, empty if it can have any value
+
+    let key = profile::strip_key(key);
+    map.iter()
+        .find(|m| m.key == key)
+        .and_then(|m| m.choices.as_ref())
+        .map(|choices| choices.as_slice())
+        .unwrap_or(&[])
+}
+
+pub fn is_current(option : &OptionsMap) -> bool {
+    //! checks that the key was added by this version of the library
+    //! and hasn't been removed yet
+
+    let lib_version = Version::from_str(env!("CARGO_PKG_VERSION")).unwrap();
+    !option.added.as_ref().map(|added| *added > lib_version).unwrap_or(false)
+    && !option.removed.as_ref().map(|removed| *removed <= lib_version).unwrap_or(false)
+}
+
+pub fn get_default(map : &[OptionsMap], key : &str) -> Option<Type> {
+    //! the value used when the key isn't set anywhere, if the map has
+    //! one for this version
+
+    map.iter()
+        .find(|m| m.key == key && is_current(m))
+        .and_then(|m| m.default.clone())
+}
+
+pub fn get_kind<'a>(map : &'a [OptionsMap], key : &str) -> Option<&'a str> {
+    //! the type the key's value should be, if the map says
+
+    let key = profile::strip_key(key);
+    map.iter()
+        .find(|m| m.key == key)
+        .and_then(|m| m.kind.as_ref())
+        .map(|kind| kind.as_str())
+}
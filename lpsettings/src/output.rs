@@ -0,0 +1,214 @@
+//! machine readable output of settings, so scripts and other
+//! tools don't need to scrape the colored text output.
+
+use std::io::Write;
+use std::str::FromStr;
+
+use failure::Error;
+use serde_json;
+use toml;
+
+use error::ErrorKind;
+use secrets;
+use crypt;
+use Type;
+
+/// what is shown instead of a value that is still encrypted
+pub const ENCRYPTED : &str = "[encrypted]";
+
+/// the names of the formats, as used on the command line
+pub const FORMATS : [&str; 4] = [ "text", "json", "toml", "raw" ];
+
+#[derive(Clone,Copy,PartialEq,Default)]
+pub enum OutputFormat {
+    /// the colored, human readable output
+    #[default]
+    Text,
+    Json,
+    Toml,
+    /// only the values, without any decoration
+    Raw,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(text : &str) -> Result<OutputFormat,Error> {
+        match text {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "toml" => Ok(OutputFormat::Toml),
+            "raw" => Ok(OutputFormat::Raw),
+            _ => Err(ErrorKind::InvalidInput(format!("'{}' is not a valid format, use one of {}",text,FORMATS.join(", "))).into()),
+        }
+    }
+}
+
+/// a single setting, as it is written out
+#[derive(Serialize)]
+pub struct Entry {
+    pub key : String,
+    pub value : Option<Type>,
+    #[serde(rename = "type")]
+    pub kind : String,
+    /// where the value came from, `local` or `global`
+    pub source : String,
+    /// the settings file a local value came from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file : Option<String>,
+    /// the other places the key is set that this value overrides
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub overrides : Vec<String>,
+    /// the description from the options map, if asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desc : Option<String>,
+}
+
+impl Entry {
+    pub fn new(key : &str, value : Option<Type>, source : &str) -> Entry {
+        //! creates the entry, masking the value if the key is secret
+        //! and hiding it if it is still encrypted
+
+        Entry {
+            key : key.to_string(),
+            kind : value.as_ref().map(type_name).unwrap_or("none").to_string(),
+            value : value.map(|value| {
+                if crypt::is_encrypted(&value) { Type::Text(ENCRYPTED.to_string()) }
+                else { secrets::mask(key,value) }
+            }),
+            source : source.to_string(),
+            file : None,
+            overrides : Vec::new(),
+            desc : None,
+        }
+    }
+
+    pub fn new_revealed(key : &str, value : Option<Type>, source : &str) -> Entry {
+        //! creates the entry without masking secret values
+
+        Entry {
+            key : key.to_string(),
+            kind : value.as_ref().map(type_name).unwrap_or("none").to_string(),
+            value,
+            source : source.to_string(),
+            file : None,
+            overrides : Vec::new(),
+            desc : None,
+        }
+    }
+}
+
+/// an option from the options map
+#[derive(Serialize)]
+pub struct Possible {
+    pub key : String,
+    pub desc : String,
+}
+
+pub fn type_name(value : &Type) -> &'static str {
+    match value {
+        Type::Text(_) => "text",
+        Type::Switch(_) => "switch",
+        Type::Int(_) => "int",
+        Type::Float(_) => "float",
+        Type::Complex(_) => "table",
+        Type::Array(_) => "array",
+        Type::None => "none",
+    }
+}
+
+pub fn write_entry<W : Write>(out : &mut W, format : OutputFormat, entry : &Entry) -> Result<(),Error> {
+    //! writes a single setting, `Raw` writes only the value.
+
+    match format {
+        OutputFormat::Json => writeln!(out,"{}",serde_json::to_string_pretty(entry)?)?,
+        OutputFormat::Toml => write!(out,"{}",to_toml(&[entry])?)?,
+        OutputFormat::Raw | OutputFormat::Text => {
+            if let Some(ref value) = entry.value {
+                writeln!(out,"{}",value)?;
+            }
+        },
+    }
+
+    Ok(())
+}
+
+pub fn write_entries<W : Write>(out : &mut W, format : OutputFormat, entries : &[Entry]) -> Result<(),Error> {
+    //! writes a list of settings.
+    //!
+    //! `Toml` writes them as a single settings file, so later entries
+    //! override earlier ones with the same key. `Raw` writes one
+    //! `key=value` per line.
+
+    match format {
+        OutputFormat::Json => writeln!(out,"{}",serde_json::to_string_pretty(entries)?)?,
+        OutputFormat::Toml => write!(out,"{}",to_toml(&entries.iter().collect::<Vec<&Entry>>())?)?,
+        OutputFormat::Raw | OutputFormat::Text => {
+            for entry in entries {
+                match entry.value {
+                    Some(ref value) => writeln!(out,"{}={}",entry.key,value)?,
+                    None => writeln!(out,"{}",entry.key)?,
+                }
+            }
+        },
+    }
+
+    Ok(())
+}
+
+pub fn write_possible<W : Write>(out : &mut W, format : OutputFormat, options : &[Possible]) -> Result<(),Error> {
+    //! writes the valid options from the options map
+
+    match format {
+        OutputFormat::Json => writeln!(out,"{}",serde_json::to_string_pretty(options)?)?,
+        OutputFormat::Toml => {
+            let mut root = toml::value::Table::new();
+            for option in options {
+                let parts : Vec<&str> = option.key.split('.').collect();
+                insert(&mut root,&parts,toml::Value::String(option.desc.to_string()));
+            }
+            write!(out,"{}",toml::to_string(&toml::Value::Table(root))?)?;
+        },
+        OutputFormat::Raw | OutputFormat::Text => {
+            for option in options {
+                writeln!(out,"{}",option.key)?;
+            }
+        },
+    }
+
+    Ok(())
+}
+
+fn to_toml(entries : &[&Entry]) -> Result<String,Error> {
+    //! builds a toml document, nesting the dotted keys into tables
+
+    let mut root = toml::value::Table::new();
+
+    for entry in entries {
+        match entry.value {
+            Some(Type::None) | None => { },
+            Some(ref value) => {
+                let parts : Vec<&str> = entry.key.split('.').collect();
+                insert(&mut root,&parts,toml::Value::try_from(value)?);
+            },
+        }
+    }
+
+    Ok(toml::to_string(&toml::Value::Table(root))?)
+}
+
+fn insert(table : &mut toml::value::Table, parts : &[&str], value : toml::Value) {
+    if parts.len() == 1 {
+        table.insert(parts[0].to_string(),value);
+        return;
+    }
+
+    let inner = table.entry(parts[0].to_string())
+        .or_insert_with(|| toml::Value::Table(toml::value::Table::new()));
+    if !inner.is_table() {
+        *inner = toml::Value::Table(toml::value::Table::new());
+    }
+    if let toml::Value::Table(ref mut inner) = *inner {
+        insert(inner,&parts[1..],value);
+    }
+}
@@ -0,0 +1,147 @@
+//! named profiles that overlay the base settings.
+//!
+//! a profile is a table in the settings file, `[profiles.ci]`, and
+//! any key inside of it takes priority over the same key in the base
+//! settings while the profile is active.
+//!
+//! the active profile is chosen, in order, by the profile passed to
+//! the call (like [`get_value`] or `--profile`), the `LOVEPACK_PROFILE`
+//! environmental variable, or the profile saved with [`use_profile`].
+
+use std::env;
+use std::collections::HashMap;
+
+use failure::Error;
+
+use store::Store;
+use scope::Scope;
+use error::ErrorKind;
+use Type;
+use { set_value, unset_value, unset_value_in };
+
+/// the key in the settings that holds the profile chosen with `use`
+pub const SETTING_KEY : &str = "lpsettings.profile";
+
+/// the environmental variable that selects the profile for a run
+pub const ENV_VAR : &str = "LOVEPACK_PROFILE";
+
+pub(crate) fn get_override() -> Option<String> {
+    //! the profile selected through the environment, if any.
+
+    match env::var(ENV_VAR) {
+        Ok(ref name) if !name.is_empty() => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+pub fn get_active() -> Result<Option<String>,Error> {
+    //! the profile that is used when none is asked for, if any.
+
+    Ok(Store::load()?.get_profile())
+}
+
+pub fn get_value(name : &str, key : &str) -> Result<Option<Type>,Error> {
+    //! gets the value with the profile active, instead of the one
+    //! from the environment or the settings.
+
+    Store::load()?.profile(Some(name)).get_value(key,&Scope::Best)
+}
+
+pub fn list() -> Result<Vec<String>,Error> {
+    //! all the profiles defined in either the local or global settings
+
+    let mut names : Vec<String> = Vec::new();
+    if let Some(Type::Complex(profiles)) = Store::load()?.get_value_raw("profiles",&Scope::Best)? {
+        names = profiles.keys().cloned().collect();
+    }
+
+    names.sort();
+    Ok(names)
+}
+
+pub fn create(name : &str) -> Result<(),Error> {
+    //! creates an empty profile in the global settings
+
+    check_name(name)?;
+    if list()?.iter().any(|profile| profile == name) {
+        return Err(ErrorKind::InvalidInput(format!("Profile '{}' already exists",name)).into());
+    }
+
+    set_value(&get_key(name),&HashMap::<String,Type>::new())?;
+    Ok(())
+}
+
+pub fn delete(name : &str) -> Result<(),Error> {
+    //! removes the profile from the local and global settings, from
+    //! whichever it was found in, if it is the saved profile then that
+    //! is cleared as well.
+
+    check_name(name)?;
+
+    let key = get_key(name);
+    let store = Store::load()?;
+    let mut found = false;
+    for scope in [Scope::Local, Scope::Global].iter() {
+        if store.get_value_raw(&key,scope)?.is_some() {
+            unset_value_in(&key,*scope)?;
+            found = true;
+        }
+    }
+    if !found {
+        return Err(ErrorKind::InvalidInput(format!("Profile '{}' doesn't exist",name)).into());
+    }
+
+    if let Some(Type::Text(saved)) = store.get_value_raw(SETTING_KEY,&Scope::Global)? {
+        if saved == name { unset_value(SETTING_KEY)?; }
+    }
+
+    Ok(())
+}
+
+pub fn use_profile(name : Option<&str>) -> Result<(),Error> {
+    //! saves the profile that should be used when none is selected,
+    //! `None` clears it.
+
+    match name {
+        Some(name) => {
+            check_name(name)?;
+            if !list()?.iter().any(|profile| profile == name) {
+                return Err(ErrorKind::InvalidInput(format!("Profile '{}' doesn't exist",name)).into());
+            }
+            set_value(SETTING_KEY,&name)?;
+        },
+        None => { unset_value(SETTING_KEY)?; },
+    }
+
+    Ok(())
+}
+
+pub fn get_key(name : &str) -> String {
+    //! the key of the profile's table
+
+    format!("profiles.{}",name)
+}
+
+pub fn is_profile_key(key : &str) -> bool {
+    //! if the key is in the table that holds the profiles
+
+    key == "profiles" || key.starts_with("profiles.")
+}
+
+pub fn strip_key(key : &str) -> &str {
+    //! removes the `profiles.NAME.` part of a key, if it has one
+
+    if let Some(rest) = key.strip_prefix("profiles.") {
+        if let Some(split) = rest.find('.') {
+            return &rest[split+1..];
+        }
+    }
+    key
+}
+
+fn check_name(name : &str) -> Result<(),Error> {
+    if name.is_empty() || name.contains('.') {
+        return Err(ErrorKind::InvalidInput(format!("'{}' is not a valid profile name",name)).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,191 @@
+//! finding the project a command is run in.
+//!
+//! the local settings belong to the project, not to whichever folder
+//! inside it the command is run from, so they are looked for in the
+//! current directory and then each parent until a folder with a local
+//! settings file is found. the search stops at the root of a
+//! repository (a folder with `.git`, `.hg` or `.svn`) or at the root
+//! of the filesystem. outside of a project the current directory is
+//! used, which is where a new local settings file is made.
+//!
+//! projects can be nested, like several games in one repository that
+//! share the settings at its root. a project inherits the values of
+//! every settings file above it, up to the same repository or
+//! filesystem root, with the closest file winning. a file with
+//! `root = true` doesn't inherit anything from above it. only the
+//! project's own file is ever written to.
+
+use std::env;
+use std::fs::File;
+use std::path::{ Path, PathBuf };
+
+use failure::Error;
+use settingsfile::{ Format, Settings, ShadowSettings };
+
+use settings::Configuration;
+use secrets;
+use Type;
+
+/// the key that stops a settings file inheriting from the ones above
+pub const ROOT_KEY : &str = "root";
+
+/// folders that mark the root of a repository, the search doesn't go
+/// above them
+const VCS_MARKERS : [&str; 3] = [".git", ".hg", ".svn"];
+
+pub fn find_root() -> Option<PathBuf> {
+    //! the project the current directory is in, if any
+
+    env::current_dir().ok().and_then(find_root_from)
+}
+
+pub fn find_root_from<P : AsRef<Path>>(start : P) -> Option<PathBuf> {
+    //! the closest folder, starting at `start` and going up, that has
+    //! a local settings file
+
+    let names = get_file_names();
+
+    for folder in start.as_ref().ancestors() {
+        if names.iter().any(|name| folder.join(name).is_file()) {
+            return Some(folder.to_path_buf());
+        }
+        if VCS_MARKERS.iter().any(|marker| folder.join(marker).exists()) {
+            return None;
+        }
+    }
+
+    None
+}
+
+/// a local settings file and its values
+pub(crate) struct Layer {
+    pub file : PathBuf,
+    pub settings : Settings<Configuration>,
+}
+
+pub fn files() -> Result<Vec<PathBuf>,Error> {
+    //! the local settings files that are used, the project's own file
+    //! first and then each one it inherits from
+
+    Ok(load_layers()?.into_iter().map(|layer| layer.file).collect())
+}
+
+pub(crate) fn load_inherited() -> Result<Vec<Layer>,Error> {
+    //! the settings files the project inherits from, closest first,
+    //! without the project's own file
+
+    let root = match find_root() {
+        Some(root) => root,
+        None => return Ok(Vec::new()),
+    };
+
+    Ok(load_layers()?.into_iter()
+        .filter(|layer| layer.file.parent() != Some(root.as_path()))
+        .collect())
+}
+
+fn load_layers() -> Result<Vec<Layer>,Error> {
+    //! every local settings file from the project up, stopping at
+    //! one marked `root`, or at a repository or filesystem root
+
+    find_files().into_iter()
+        .map(|file| {
+            let settings = load(&file)
+                .map_err(|error| error.context(format!("Cannot read the settings in {}",file.display())))?;
+            Ok(Layer { file, settings })
+        }).collect()
+}
+
+pub(crate) fn find_files() -> Vec<PathBuf> {
+    //! the files `files` returns, without reading them as settings
+    //! first. a file that can't be read doesn't stop the search, it
+    //! is listed and the ones above it are too.
+
+    let root = match find_root() {
+        Some(root) => root,
+        None => return Vec::new(),
+    };
+
+    let config = Configuration::new();
+    let name = config.get_local_filename().unwrap_or_else(|| config.get_filename());
+
+    let mut files : Vec<PathBuf> = Vec::new();
+    for folder in root.ancestors() {
+        let file = folder.join(&name);
+        if file.is_file() {
+            let stop = match load(&file) {
+                Ok(settings) => settings.get_value(ROOT_KEY) == Some(Type::Switch(true)),
+                Err(_) => false,
+            };
+
+            files.push(file);
+            if stop { break; }
+        }
+        if VCS_MARKERS.iter().any(|marker| folder.join(marker).exists()) {
+            break;
+        }
+    }
+
+    files
+}
+
+pub(crate) fn load(file : &Path) -> Result<Settings<Configuration>,Error> {
+    //! reads a settings file
+
+    let handle = File::open(file)?;
+    Settings::create_from(&handle,Configuration::new())
+}
+
+pub(crate) fn get_folder() -> PathBuf {
+    //! the folder the local settings are read from and written to
+
+    match find_root() {
+        Some(root) => root,
+        None => env::current_dir().unwrap_or_default(),
+    }
+}
+
+pub(crate) fn get_file_in(folder : &Path, secret : bool) -> PathBuf {
+    //! the local settings, or secrets, file of a project in `folder`
+
+    let config = if secret { Configuration::secrets() } else { Configuration::new() };
+    folder.join(config.get_local_filename().unwrap_or_else(|| config.get_filename()))
+}
+
+pub(crate) fn load_in(folder : &Path, secret : bool) -> Result<ShadowSettings<Configuration>,Error> {
+    //! the local settings, or secrets, of the project in `folder`,
+    //! which doesn't have to be the project the current directory is
+    //! in. only the local values are read.
+
+    let config = if secret { Configuration::secrets() } else { Configuration::new() };
+    let mut store = ShadowSettings::new(config);
+
+    let file = get_file_in(folder,secret);
+    if file.exists() {
+        store.load_local_from(&mut File::open(&file)?)
+            .map_err(|error| error.context(format!("Cannot read the settings in {}",file.display())))?;
+    }
+
+    Ok(store)
+}
+
+pub(crate) fn save_in(store : &ShadowSettings<Configuration>, folder : &Path, secret : bool) -> Result<(),Error> {
+    //! saves the local values of `store` as the project in `folder`.
+    //! the file is only written when there is something in it, an
+    //! empty file would make the folder a project.
+
+    let file = get_file_in(folder,secret);
+    if store.keys_local().is_empty() && !file.exists() { return Ok(()); }
+
+    let handle = if secret { secrets::open_restricted(&file.display().to_string())? } else { File::create(&file)? };
+    store.save_local_to(&handle)
+}
+
+fn get_file_names() -> Vec<String> {
+    //! the local settings and secrets file names, either one makes
+    //! the folder a project
+
+    [Configuration::new(), Configuration::secrets()].iter()
+        .map(|config| config.get_local_filename().unwrap_or_else(|| config.get_filename()))
+        .collect()
+}
@@ -0,0 +1,205 @@
+//! asking the user for input.
+//!
+//! anything interactive takes a `Prompter` instead of using stdin and
+//! stdout directly, so it can be driven from a script or checked in
+//! tests. `Terminal` is the one used by the cli.
+//!
+//! only `ask` has to be written, the other kinds of questions (yes or
+//! no, picking from a menu) are built on it.
+
+use std::collections::VecDeque;
+use std::io::{ self, BufRead, IsTerminal, Write };
+
+use failure::Error;
+
+use error::ErrorKind;
+#[cfg(feature = "cli")]
+use console;
+
+pub trait Prompter {
+    /// asks the question and returns the answer, without the line
+    /// ending. an empty answer means the user didn't give one.
+    fn ask(&mut self, question : &str) -> Result<String,Error>;
+
+    /// asks without showing what is typed, for secret values
+    fn ask_hidden(&mut self, question : &str) -> Result<String,Error> {
+        self.ask(question)
+    }
+
+    /// shows a message that doesn't need an answer
+    fn say(&mut self, _message : &str) { }
+
+    /// asks a yes or no question, an empty answer is the default.
+    /// asks again until it gets an answer it understands.
+    fn confirm(&mut self, question : &str, default : bool) -> Result<bool,Error> {
+        Ok(self.confirm_optional(question,default)?.unwrap_or(default))
+    }
+
+    /// like `confirm`, but an empty answer is `None`, the default is
+    /// only shown as the suggestion.
+    fn confirm_optional(&mut self, question : &str, default : bool) -> Result<Option<bool>,Error> {
+        let question = format!("{} [{}]",question,if default { "Y/n" } else { "y/N" });
+        loop {
+            match self.ask(&question)?.trim().to_lowercase().as_str() {
+                "" => return Ok(None),
+                "y" | "yes" | "true" => return Ok(Some(true)),
+                "n" | "no" | "false" => return Ok(Some(false)),
+                answer => self.say(&format!("'{}' isn't yes or no",answer)),
+            }
+        }
+    }
+
+    /// asks to pick one of the choices from a numbered menu, either by
+    /// number or by the choice itself. an empty answer picks nothing,
+    /// and anything else asks again.
+    fn choose(&mut self, question : &str, choices : &[String]) -> Result<Option<usize>,Error> {
+        for (index,choice) in choices.iter().enumerate() {
+            self.say(&format!("  {}) {}",index+1,choice));
+        }
+
+        let question = format!("{} [1-{}]",question,choices.len());
+        loop {
+            let answer = self.ask(&question)?;
+            let answer = answer.trim();
+            if answer.is_empty() { return Ok(None); }
+
+            if let Ok(number) = answer.parse::<usize>() {
+                if number >= 1 && number <= choices.len() { return Ok(Some(number-1)); }
+            }
+            if let Some(index) = choices.iter().position(|choice| choice == answer) {
+                return Ok(Some(index));
+            }
+            self.say(&format!("'{}' isn't one of the choices, pick 1 to {}",answer,choices.len()));
+        }
+    }
+
+    /// if anyone can answer, a terminal reading from a pipe can't
+    fn is_interactive(&self) -> bool { true }
+}
+
+/// asks on stdout and reads the answers from stdin.
+#[derive(Default)]
+pub struct Terminal;
+
+impl Terminal {
+    pub fn new() -> Terminal {
+        Terminal
+    }
+}
+
+impl Prompter for Terminal {
+    fn ask(&mut self, question : &str) -> Result<String,Error> {
+        print!("{}: ",question);
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        if io::stdin().lock().read_line(&mut input)? == 0 {
+            return Err(ErrorKind::InvalidInput(format!("No answer for '{}', stdin was closed",question)).into());
+        }
+
+        // trims the \r\n at the end of the string.
+        Ok(input.trim_end_matches(&['\r','\n'][..]).to_string())
+    }
+
+    #[cfg(feature = "cli")]
+    fn ask_hidden(&mut self, question : &str) -> Result<String,Error> {
+        let term = console::Term::stdout();
+        term.write_str(&format!("{}: ",question))?;
+        Ok(term.read_secure_line()?)
+    }
+
+    /// without the `cli` feature there is nothing to hide the input
+    /// with, and secret values aren't echoed instead
+    #[cfg(not(feature = "cli"))]
+    fn ask_hidden(&mut self, question : &str) -> Result<String,Error> {
+        Err(ErrorKind::InvalidInput(format!("Cannot ask for '{}' without showing what is typed, the `cli` feature isn't enabled. Give the answer up front instead",question)).into())
+    }
+
+    fn say(&mut self, message : &str) {
+        println!("{}",message);
+    }
+
+    fn is_interactive(&self) -> bool {
+        io::stdin().is_terminal()
+    }
+}
+
+/// answers with a list of answers given up front, in order.
+#[derive(Default)]
+pub struct Scripted {
+    answers : VecDeque<String>,
+}
+
+impl Scripted {
+    pub fn new<S : AsRef<str>>(answers : &[S]) -> Scripted {
+        Scripted {
+            answers : answers.iter().map(|answer| answer.as_ref().to_string()).collect(),
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        //! how many answers haven't been used
+        self.answers.len()
+    }
+}
+
+impl Prompter for Scripted {
+    fn ask(&mut self, question : &str) -> Result<String,Error> {
+        self.answers.pop_front()
+            .ok_or_else(|| ErrorKind::InvalidInput(format!("No answer for '{}', the script ran out",question)).into())
+    }
+}
+
+/// keeps every question and answer that goes through another prompter.
+pub struct Recording<P : Prompter> {
+    inner : P,
+    asked : Vec<(String,String)>,
+    said : Vec<String>,
+}
+
+impl<P : Prompter> Recording<P> {
+    pub fn new(inner : P) -> Recording<P> {
+        Recording { inner, asked : Vec::new(), said : Vec::new() }
+    }
+
+    pub fn asked(&self) -> &[(String,String)] {
+        //! the questions and their answers, in the order they were asked
+        &self.asked
+    }
+
+    pub fn questions(&self) -> Vec<&str> {
+        self.asked.iter().map(|(question,_)| question.as_str()).collect()
+    }
+
+    pub fn said(&self) -> &[String] {
+        //! the messages that were shown
+        &self.said
+    }
+
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+}
+
+impl<P : Prompter> Prompter for Recording<P> {
+    fn ask(&mut self, question : &str) -> Result<String,Error> {
+        let answer = self.inner.ask(question)?;
+        self.asked.push((question.to_string(),answer.to_string()));
+        Ok(answer)
+    }
+
+    fn ask_hidden(&mut self, question : &str) -> Result<String,Error> {
+        let answer = self.inner.ask_hidden(question)?;
+        self.asked.push((question.to_string(),answer.to_string()));
+        Ok(answer)
+    }
+
+    fn say(&mut self, message : &str) {
+        self.inner.say(message);
+        self.said.push(message.to_string());
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.inner.is_interactive()
+    }
+}
@@ -0,0 +1,108 @@
+//! which settings file a value is read from or written to.
+//!
+//! the scope is passed to the functions that need it, the
+//! `LOVEPACK_SETTINGS_LOCATION` environmental variable is only read
+//! as an override from outside, with `Scope::from_env`, and is never
+//! set by the library.
+
+use std::fmt;
+use std::env;
+use std::str::FromStr;
+
+use failure::Error;
+
+use error::ErrorKind;
+
+/// the environmental variable that can pick the scope from outside
+pub const ENV_VAR : &str = "LOVEPACK_SETTINGS_LOCATION";
+
+/// a location to be used to determine where to load a value from
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Default)]
+pub enum Scope {
+
+    /// whatever the default recommeneded location is
+    #[default]
+    Best,
+    /// the local location
+    Local,
+    /// the global location
+    Global,
+    /// the system-wide settings, below the global ones. only
+    /// written to when asked for explicitly
+    System,
+}
+
+impl Scope {
+    pub fn from_env() -> Option<Scope> {
+        //! the scope set in `LOVEPACK_SETTINGS_LOCATION`, if any.
+        //!
+        //! anything other than `global`, `system` or `best` is local,
+        //! which is how the variable has always been read.
+
+        match env::var(ENV_VAR) {
+            Err(_) => None,
+            Ok(value) => match value.parse() {
+                Ok(scope) => Some(scope),
+                Err(_) => Some(Scope::Local),
+            }
+        }
+    }
+
+    pub fn from_flags(local : bool, global : bool, system : bool) -> Scope {
+        //! the scope from the `--local`, `--global` and `--system`
+        //! flags, falling back to the environmental variable and then
+        //! `Best`
+
+        if local { Scope::Local }
+        else if global { Scope::Global }
+        else if system { Scope::System }
+        else { Scope::from_env().unwrap_or_default() }
+    }
+
+    pub fn is_local(&self) -> bool {
+        *self == Scope::Local
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scope::Best => "best",
+            Scope::Local => "local",
+            Scope::Global => "global",
+            Scope::System => "system",
+        }
+    }
+
+    pub fn to_string_cap(&self) -> String {
+        match self {
+            Scope::Best => "".to_string(),
+            Scope::Local => "Locally".to_string(),
+            Scope::Global => "Globally".to_string(),
+            Scope::System => "System-wide".to_string(),
+        }
+    }
+}
+
+impl FromStr for Scope {
+    type Err = Error;
+
+    fn from_str(s : &str) -> Result<Scope,Error> {
+        match s {
+            "best" => Ok(Scope::Best),
+            "local" => Ok(Scope::Local),
+            "global" => Ok(Scope::Global),
+            "system" => Ok(Scope::System),
+            _ => Err(ErrorKind::InvalidInput(format!("'{}' isn't a scope, use best, local, global or system",s)).into()),
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Scope::Best => write!(f,""),
+            Scope::Local => write!(f,"locally"),
+            Scope::Global => write!(f,"globally"),
+            Scope::System => write!(f,"system-wide"),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! handling for keys marked `secret` in the options map.
+//!
+//! secret values (like repository tokens) are not stored in the
+//! regular settings file but in a separate `lovepack.secrets.toml`
+//! that is only readable by the user, and are masked whenever they
+//! are displayed.
+
+use std::fs::{ self, File };
+use std::path::Path;
+#[cfg(unix)]
+use std::sync::Mutex;
+
+use failure::Error;
+use settingsfile::{ Format, ShadowSettings };
+
+use settings::Configuration;
+use map;
+use Type;
+
+/// what is shown instead of a secret value
+pub const MASK : &str = "****";
+
+/// the secrets files that were already warned about
+#[cfg(unix)]
+static WARNED : Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// the warnings that haven't been taken with `take_warnings` yet
+#[cfg(unix)]
+static PENDING : Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub fn is_secret(key : &str) -> bool {
+    //! checks the options map if this key (or one of its parents)
+    //! is marked as secret.
+
+    map::is_secret(map::get_options_map(),key)
+}
+
+pub fn mask(key : &str, value : Type) -> Type {
+    //! replaces the value with the mask if the key is secret
+
+    if is_secret(key) { Type::Text(MASK.to_string()) }
+    else { value }
+}
+
+pub(crate) fn load() -> Result<ShadowSettings<Configuration>,Error> {
+    //! loads the secrets store, warning if anyone else can read it.
+
+    let config = Configuration::secrets();
+    check_permissions(&config.get_path_and_file());
+    check_permissions(&config.get_local_path_and_filename());
+
+    let mut store = ShadowSettings::new(config);
+    store.load_global()?;
+    store.load_local()?;
+    Ok(store)
+}
+
+pub(crate) fn save(store : &ShadowSettings<Configuration>) -> Result<(),Error> {
+    //! saves the secrets store. every file is opened restricted before
+    //! anything is written, so a file that doesn't exist yet is only
+    //! ever created readable by the user, whatever made the change
+    //! (a set, init, import or the tui).
+
+    let config = Configuration::secrets();
+    fs::create_dir_all(config.get_path())?;
+    store.save_global_to(&open_restricted(&config.get_path_and_file())?)?;
+
+    // the local file is only written when there is one, an empty file
+    // would make the folder a project
+    let local = config.get_local_path_and_filename();
+    if !store.keys_local().is_empty() || Path::new(&local).exists() {
+        store.save_local_to(&open_restricted(&local)?)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+pub(crate) fn open_restricted(path : &str) -> Result<File,Error> {
+    //! opens the file for writing, replacing what is in it. a new file
+    //! is created readable only by the user.
+
+    use std::os::unix::fs::OpenOptionsExt;
+
+    Ok(fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn open_restricted(path : &str) -> Result<File,Error> {
+    Ok(File::create(path)?)
+}
+
+#[cfg(unix)]
+pub fn take_warnings() -> Vec<String> {
+    //! the warnings about secrets files that other users can read,
+    //! found since this was last called. each file is only warned
+    //! about once. the cli writes them after the command's output.
+
+    let mut pending = PENDING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    pending.drain(..).collect()
+}
+
+#[cfg(not(unix))]
+pub fn take_warnings() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(unix)]
+fn check_permissions(path : &str) {
+    //! keeps a warning for `take_warnings` if anyone else can read
+    //! the file, and logs it.
+
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Ok(metadata) = fs::metadata(path) {
+        if metadata.permissions().mode() & 0o077 != 0 {
+            let mut warned = WARNED.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if !warned.iter().any(|file| file == path) {
+                warned.push(path.to_string());
+
+                let warning = format!("{} can be read by other users, it should only be readable by you (chmod 600 {})",path,path);
+                warn!("{}",warning);
+                PENDING.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(warning);
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_ : &str) { }
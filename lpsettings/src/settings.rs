@@ -1,11 +1,13 @@
-/// contains the struct to use for configuration
-/// and settings physical file definition
+//! contains the struct to use for configuration
+//! and settings physical file definition
 
 // settings stuff
 use settingsfile::{ Format, SettingsRaw, SupportedType };
 // for implementations
 use serde;
 use failure::Error;
+use error::ErrorKind;
+use project;
 // format to use
 use toml;
 
@@ -15,30 +17,63 @@ use toml;
 /// 
 /// this implementation is very basic, just using 
 /// toml-rs and serde-rs to write and read to toml
-#[derive(Clone)]
+#[derive(Clone,Default)]
 pub struct Configuration { 
+    /// points at the secrets file instead of the settings file
+    secrets : bool,
+}
+
+impl Configuration {
+    pub fn new() -> Configuration {
+        Configuration { secrets : false }
+    }
+
+    pub fn secrets() -> Configuration {
+        //! the configuration for the separate file that
+        //! holds the values of keys marked `secret`
+        
+        Configuration { secrets : true }
+    }
 }
 
 impl Format for Configuration {
-    fn filename(&self) -> String { "lovepack".to_string() }
+    fn filename(&self) -> String { 
+        if self.secrets { "lovepack.secrets".to_string() } 
+        else { "lovepack".to_string() }
+    }
     fn folder(&self) -> String { ".lovepack".to_string() }
     fn extension(&self) -> Option<String> { Some("toml".to_string()) }
 
+    fn get_local_path_and_filename(&self) -> String {
+        // the project's folder, which may be above the current
+        // directory, see `project`
+        let filename = self.get_local_filename().unwrap_or_else(|| self.get_filename());
+        project::get_folder().join(filename).display().to_string()
+    }
+
     fn from_str<T>(&self,buffer:&str) -> Result<SettingsRaw,Error> 
         where T : Format + Clone 
     {
-        match toml::de::from_str(&buffer) {
+        match toml::de::from_str(buffer) {
             Ok(result) => Ok(result),
-            Err(error) => Err(format_err!("{}",error)),
+            Err(error) => Err(ErrorKind::Parse(error.to_string()).into()),
         }
     }
 
-    fn to_string<T:Sized>(&self,object:&T) -> Result<String,Error>
+    fn to_string<T>(&self,object:&T) -> Result<String,Error>
         where T : SupportedType + serde::ser::Serialize, 
     {
-        match toml::ser::to_string(object) {
+        // goes through `toml::Value` first because it always writes the 
+        // plain values of a table before its subtables, serializing the
+        // `HashMap` directly fails depending on the order of the keys.
+        let value = match toml::Value::try_from(object) {
+            Ok(value) => value,
+            Err(error) => return Err(format_err!("{}",error)),
+        };
+
+        match toml::ser::to_string(&value) {
                 Ok(result) => Ok(result),
-                Err(error) => Err(format_err!("{}",error)),
+                Err(error) => Err(ErrorKind::Parse(error.to_string()).into()),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,278 @@
+//! everything needed to read a value, the settings, the secrets
+//! store, the project settings that are inherited and the system
+//! settings, loaded together.
+
+use failure::Error;
+use settingsfile::{ ShadowSettings, Settings };
+
+use settings::Configuration;
+use scope::Scope;
+use interpolate;
+use environment;
+use profile;
+use map;
+use secrets;
+use crypt;
+use system;
+use project::{ self, Layer };
+use Type;
+use load_settings;
+
+/// where a value comes from, in the order they are used
+#[derive(Clone,Copy,PartialEq,Debug)]
+pub enum Source {
+    /// a `LOVEPACK_` environmental variable
+    Env,
+    /// the active profile
+    Profile,
+    Local,
+    Global,
+    /// the system-wide settings
+    System,
+    /// the default in the options map
+    Default,
+}
+
+impl Source {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Source::Env => "env",
+            Source::Profile => "profile",
+            Source::Local => "local",
+            Source::Global => "global",
+            Source::System => "system",
+            Source::Default => "default",
+        }
+    }
+}
+
+pub struct Store {
+    settings : ShadowSettings<Configuration>,
+    secrets : ShadowSettings<Configuration>,
+    system : Settings<Configuration>,
+    /// the project settings files above the local one, closest first
+    inherited : Vec<Layer>,
+    /// the profile that was asked for, over the environment and the
+    /// saved one
+    profile : Option<String>,
+}
+
+impl Store {
+    pub fn load() -> Result<Store,Error> {
+        Ok(Store {
+            settings : load_settings(false)?,
+            secrets : load_settings(true)?,
+            system : system::load()?,
+            inherited : project::load_inherited()?,
+            profile : None,
+        })
+    }
+
+    pub fn profile(mut self, profile : Option<&str>) -> Store {
+        //! uses this profile instead of the one from the environment
+        //! or the settings, `None` keeps those.
+
+        if let Some(profile) = profile {
+            self.profile = Some(profile.to_string());
+        }
+        self
+    }
+
+    pub fn get_value(&self, key : &str, location : &Scope) -> Result<Option<Type>,Error> {
+        //! gets the value with all references expanded
+
+        self.get_value_expanded(key,location,false)
+    }
+
+    pub fn get_value_masked(&self, key : &str, location : &Scope) -> Result<Option<Type>,Error> {
+        //! gets the value with all references expanded, but with the
+        //! mask in place of any secret value it references, for when
+        //! the value is going to be shown.
+
+        self.get_value_expanded(key,location,true)
+    }
+
+    fn get_value_expanded(&self, key : &str, location : &Scope, mask : bool) -> Result<Option<Type>,Error> {
+        match self.get_value_raw(key,location)? {
+            None => Ok(None),
+            Some(value) => Ok(Some(self.expand(key,value,mask)?)),
+        }
+    }
+
+    pub fn expand(&self, key : &str, value : Type, mask : bool) -> Result<Type,Error> {
+        //! expands the references in the value of `key`, with the mask
+        //! in place of referenced secret values if `mask` is set
+
+        let lookup = |key : &str| match self.get_value_raw(key,&Scope::Best)? {
+            Some(_) if mask && secrets::is_secret(key) => Ok(Some(Type::Text(secrets::MASK.to_string()))),
+            value => Ok(value),
+        };
+        interpolate::expand(key,value,&lookup)
+    }
+
+    pub fn get_value_raw(&self, key : &str, location : &Scope) -> Result<Option<Type>,Error> {
+        //! gets the value as it is written in the file, only
+        //! decrypting it. 
+        //!
+        //! if a profile is active then its value is used first. for the
+        //! `Best` location an environmental variable is used before
+        //! anything else, and the default from the options map when
+        //! the key isn't set anywhere.
+
+        if *location == Scope::Best {
+            if let Some(value) = environment::get_value(key) {
+                return Ok(Some(value));
+            }
+        }
+
+        let value = self.get_value_unprofiled(key,location)?;
+
+        let value = match self.get_profile() {
+            Some(ref profile) if !key.starts_with("profiles.") => {
+                match (self.get_value_unprofiled(&format!("{}.{}",profile::get_key(profile),key),location)?, value) {
+                    (Some(overlay), Some(base)) => Some(Store::overlay(base,overlay)),
+                    (Some(overlay), None) => Some(overlay),
+                    (None, value) => value,
+                }
+            },
+            _ => value,
+        };
+
+        match value {
+            None if *location == Scope::Best => Ok(map::get_default(map::get_options_map(),key)),
+            value => Ok(value),
+        }
+    }
+
+    fn overlay(base : Type, overlay : Type) -> Type {
+        //! puts the profile's value over the base one. tables are
+        //! merged at every level, so only the keys the profile has are
+        //! replaced.
+
+        match (base, overlay) {
+            (Type::Complex(mut base), Type::Complex(overlay)) => {
+                for (key,value) in overlay {
+                    let value = match base.remove(&key) {
+                        Some(below) => Store::overlay(below,value),
+                        None => value,
+                    };
+                    base.insert(key,value);
+                }
+                Type::Complex(base)
+            },
+            (_, overlay) => overlay,
+        }
+    }
+
+    pub fn get_source(&self, key : &str) -> Result<Option<Source>,Error> {
+        //! where the best value for this key comes from
+
+        if environment::get_value(key).is_some() {
+            return Ok(Some(Source::Env));
+        }
+
+        if let Some(profile) = self.get_profile() {
+            let profile_key = format!("{}.{}",profile::get_key(&profile),key);
+            if self.get_value_unprofiled(&profile_key,&Scope::Best)?.is_some() {
+                return Ok(Some(Source::Profile));
+            }
+        }
+
+        if self.get_value_unprofiled(key,&Scope::Local)?.is_some() {
+            Ok(Some(Source::Local))
+        } else if self.get_value_unprofiled(key,&Scope::Global)?.is_some() {
+            Ok(Some(Source::Global))
+        } else if self.get_value_unprofiled(key,&Scope::System)?.is_some() {
+            Ok(Some(Source::System))
+        } else if map::get_default(map::get_options_map(),key).is_some() {
+            Ok(Some(Source::Default))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn get_profile(&self) -> Option<String> {
+        //! the active profile, the one that was asked for, the one in
+        //! the environment or the one saved in the settings
+
+        if let Some(ref profile) = self.profile {
+            return Some(profile.to_string());
+        }
+        if let Some(profile) = profile::get_override() {
+            return Some(profile);
+        }
+
+        match self.settings.get_value(profile::SETTING_KEY).or_else(|| self.system.get_value(profile::SETTING_KEY)) {
+            Some(Type::Text(profile)) => Some(profile),
+            _ => None,
+        }
+    }
+
+    pub fn keys(&self, location : &Scope) -> Vec<String> {
+        //! every key that is set in the scope, sorted. the local scope
+        //! has the keys of the projects it inherits from too.
+
+        let mut keys : Vec<String> = Vec::new();
+        if *location == Scope::Best || *location == Scope::Local {
+            keys.append(&mut self.settings.keys_local());
+            keys.append(&mut self.secrets.keys_local());
+            for layer in self.inherited.iter() {
+                keys.append(&mut layer.settings.keys());
+            }
+        }
+        if *location == Scope::Best || *location == Scope::Global {
+            keys.append(&mut self.settings.keys_global());
+            keys.append(&mut self.secrets.keys_global());
+        }
+        if *location == Scope::Best || *location == Scope::System {
+            keys.append(&mut self.system.keys());
+        }
+
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    pub fn get_stored(&self, key : &str, location : &Scope) -> Option<Type> {
+        //! the value as it is in the file, still encrypted, without the
+        //! environment, the profile or the defaults
+
+        // the system settings are below everything else
+        match location {
+            Scope::Best => self.get_local(key)
+                .or_else(|| self.get_global(key))
+                .or_else(|| self.system.get_value(key)),
+            Scope::Local => self.get_local(key),
+            Scope::Global => self.get_global(key),
+            Scope::System => self.system.get_value(key),
+        }
+    }
+
+    fn get_value_unprofiled(&self, key : &str, location : &Scope) -> Result<Option<Type>,Error> {
+        match self.get_stored(key,location) {
+            Some(value) => Ok(Some(crypt::decrypt(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_local(&self, key : &str) -> Option<Type> {
+        //! the project's own settings, then the ones it inherits
+
+        Store::get_from(&self.secrets,&self.settings,key,ShadowSettings::get_value_local)
+            .or_else(|| self.inherited.iter().filter_map(|layer| layer.settings.get_value(key)).next())
+    }
+
+    fn get_global(&self, key : &str) -> Option<Type> {
+        Store::get_from(&self.secrets,&self.settings,key,ShadowSettings::get_value_global)
+    }
+
+    fn get_from<F>(secrets : &ShadowSettings<Configuration>, settings : &ShadowSettings<Configuration>, key : &str, get : F) -> Option<Type> 
+        where F : Fn(&ShadowSettings<Configuration>, &str) -> Option<Type>
+    {
+        //! the value from the secrets store if the key is secret, or
+        //! from the settings
+
+        let value = if secrets::is_secret(key) { get(secrets,key) } else { None };
+        value.or_else(|| get(settings,key))
+    }
+}
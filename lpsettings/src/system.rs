@@ -0,0 +1,148 @@
+//! the system-wide settings, defaults shared by every user of the
+//! machine, like a lab or a build box that ships `lprun.repo.links`.
+//!
+//! the file is `lovepack.toml` in `/etc/lovepack` (`%PROGRAMDATA%\lovepack`
+//! on windows) or the folder in `LOVEPACK_SYSTEM_DIR`. its values are
+//! used below the user's global settings, and it is only written to
+//! when the `System` scope is asked for, never by `Best`.
+
+use std::env;
+use std::fs::{ self, File };
+use std::path::{ Path, PathBuf };
+
+use failure::Error;
+use settingsfile::{ Settings, Format };
+
+use error::ErrorKind;
+use settings::Configuration;
+use secrets;
+use history;
+use Type;
+
+/// the environmental variable that moves the system folder
+pub const ENV_VAR : &str = "LOVEPACK_SYSTEM_DIR";
+
+pub fn get_folder() -> PathBuf {
+    //! the folder the system settings are kept in
+
+    match env::var(ENV_VAR) {
+        Ok(ref folder) if !folder.is_empty() => PathBuf::from(folder),
+        _ => default_folder(),
+    }
+}
+
+#[cfg(windows)]
+fn default_folder() -> PathBuf {
+    let data = env::var("PROGRAMDATA").unwrap_or_else(|_| "C:\\ProgramData".to_string());
+    PathBuf::from(data).join("lovepack")
+}
+
+#[cfg(not(windows))]
+fn default_folder() -> PathBuf {
+    PathBuf::from("/etc/lovepack")
+}
+
+pub fn get_file() -> PathBuf {
+    let config = Configuration::new();
+    let mut name = config.filename();
+    if let Some(extension) = config.extension() {
+        name = format!("{}.{}",name,extension);
+    }
+
+    get_folder().join(name)
+}
+
+pub(crate) fn load() -> Result<Settings<Configuration>,Error> {
+    //! loads the system settings, which are empty if the file
+    //! doesn't exist
+
+    let path = get_file();
+    match File::open(&path) {
+        Ok(file) => Settings::create_from(&file,Configuration::new())
+            .map_err(|error| error.context(format!("Cannot read the system settings, {}",path.display())).into()),
+        Err(_) => Ok(Settings::new(Configuration::new())),
+    }
+}
+
+pub(crate) fn save(settings : &Settings<Configuration>) -> Result<(),Error> {
+    let path = get_file();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| Error::from(error).context(format!("Cannot create {}",parent.display())))?;
+    }
+
+    let file = File::create(&path)
+        .map_err(|error| Error::from(error).context(format!("Cannot write the system settings, {}",path.display())))?;
+    settings.save_to(&file)
+}
+
+fn check_not_secret(key : &str) -> Result<(),Error> {
+    //! secrets are only kept per user
+
+    if secrets::is_secret(key) {
+        return Err(ErrorKind::InvalidInput(format!("'{}' is secret and can't be set system-wide",key)).into());
+    }
+    Ok(())
+}
+
+pub(crate) fn set_value(action : &str, key : &str, value : &Type) -> Result<Option<Type>,Error> {
+    //! sets the value in the system settings, returns the old value
+
+    check_not_secret(key)?;
+
+    let mut settings = load()?;
+    let old_value = settings.get_value(key);
+    settings.set_value(key,value)?;
+    save(&settings)?;
+
+    history::record(&[history::Change::new(action,"system",&get_file().display().to_string(),
+        key,old_value.clone(),Some(value.clone()))]);
+
+    Ok(old_value)
+}
+
+pub(crate) fn unset_value(key : &str) -> Result<Option<Type>,Error> {
+    //! removes the value from the system settings, returns the value
+    //! that was removed
+
+    let mut settings = load()?;
+    let old_value = settings.delete_key(key);
+    save(&settings)?;
+
+    if old_value.is_some() {
+        history::record(&[history::Change::new("unset","system",&get_file().display().to_string(),
+            key,old_value.clone(),None)]);
+    }
+
+    Ok(old_value)
+}
+
+pub(crate) fn import(path : &Path) -> Result<Vec<String>,Error> {
+    //! imports all the values from another settings file into the
+    //! system settings
+
+    let file = File::open(path)?;
+    let imported = Settings::create_from(&file,Configuration::new())?;
+
+    let mut keys = imported.keys();
+    keys.sort();
+    for key in keys.iter() {
+        check_not_secret(key)?;
+    }
+
+    let mut settings = load()?;
+    let file_name = get_file().display().to_string();
+    let mut changes : Vec<history::Change> = Vec::new();
+    for key in keys.iter() {
+        if let Some(value) = imported.get_value(key) {
+            let old_value = settings.get_value(key);
+            settings.set_value(key,&value)?;
+            changes.push(history::Change::new("import","system",&file_name,key,old_value,Some(value)));
+        }
+    }
+
+    save(&settings)?;
+    history::record(&changes);
+
+    Ok(keys)
+}
@@ -5,32 +5,427 @@
 //! 
 //! this is the first one though, so we shall see how this 
 //! changes and evolves over time.
+//!
+//! each kind of text has a `Role`, and the `Theme` has a style for
+//! each role. the theme starts from one of the `PRESETS` and can be
+//! changed with the `lpsettings.theme.*` settings or a theme file,
+//! see `load_theme`.
+//!
+//! colors are only used when the stream that is written to is a
+//! terminal, stdout for the output and stderr for errors. this can be
+//! changed with `--color auto|always|never`, the `NO_COLOR` and
+//! `CLICOLOR_FORCE` environmental variables, or the `lpsettings.color`
+//! setting, in that order.
 
-use ansi_term::Colour;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::{ self, IsTerminal, Read };
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+
+use ansi_term::{ Colour, Style };
+use failure::Error;
+use toml;
+
+use error::ErrorKind;
+use scope::Scope;
+use store::Store;
+use { Type, get_folder };
+
+/// the values `--color` and `lpsettings.color` can have
+pub const COLOR_CHOICES : [&str; 3] = [ "auto", "always", "never" ];
+
+#[derive(Clone,Copy,PartialEq,Debug)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = Error;
+
+    fn from_str(text : &str) -> Result<ColorChoice,Error> {
+        match text.to_lowercase().as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(ErrorKind::InvalidInput(format!("'{}' is not a color choice, use one of {}",text,COLOR_CHOICES.join(", "))).into()),
+        }
+    }
+}
+
+impl fmt::Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorChoice::Auto => write!(f,"auto"),
+            ColorChoice::Always => write!(f,"always"),
+            ColorChoice::Never => write!(f,"never"),
+        }
+    }
+}
+
+/// where text is written, `auto` checks if it is a terminal
+#[derive(Clone,Copy,PartialEq,Debug)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            Stream::Stdout => io::stdout().is_terminal(),
+            Stream::Stderr => io::stderr().is_terminal(),
+        }
+    }
+}
+
+// the color choice, worked out the first time it is needed
+const UNKNOWN : usize = 0;
+const ON : usize = 1;
+const OFF : usize = 2;
+const AUTO : usize = 3;
+static COLORS : AtomicUsize = AtomicUsize::new(UNKNOWN);
+
+pub fn set_color(choice : ColorChoice) {
+    //! chooses if colors are used, overriding the environment and
+    //! the settings. `Auto` goes back to checking them.
+
+    let state = match choice {
+        ColorChoice::Always => ON,
+        ColorChoice::Never => OFF,
+        ColorChoice::Auto => UNKNOWN,
+    };
+    COLORS.store(state,Ordering::Relaxed);
+}
+
+pub fn colors_enabled() -> bool {
+    //! if the theme adds colors to the output on stdout
+
+    colors_enabled_on(Stream::Stdout)
+}
+
+pub fn colors_enabled_on(stream : Stream) -> bool {
+    //! if the theme adds colors to text written to the stream
+
+    let state = match COLORS.load(Ordering::Relaxed) {
+        UNKNOWN => {
+            let state = match get_color_choice() {
+                ColorChoice::Always => ON,
+                ColorChoice::Never => OFF,
+                ColorChoice::Auto => AUTO,
+            };
+            COLORS.store(state,Ordering::Relaxed);
+            state
+        },
+        state => state,
+    };
+
+    match state {
+        ON => true,
+        OFF => false,
+        _ => stream.is_terminal(),
+    }
+}
+
+fn get_color_choice() -> ColorChoice {
+    //! the choice from the environment or the settings, `Auto` is
+    //! left for each stream to resolve
+
+    // https://no-color.org
+    if env::var("NO_COLOR").map(|value| !value.is_empty()).unwrap_or(false) {
+        return ColorChoice::Never;
+    }
+    if env::var("CLICOLOR_FORCE").map(|value| !value.is_empty() && value != "0").unwrap_or(false) {
+        return ColorChoice::Always;
+    }
+
+    match ::get_value("lpsettings.color") {
+        Ok(Some(Type::Text(text))) => text.parse().unwrap_or(ColorChoice::Auto),
+        _ => ColorChoice::Auto,
+    }
+}
+
+/// the names of the roles, as used in the settings and theme files
+pub const ROLES : [&str; 8] = [ "key", "value", "value_set", "error", "comment", "heading", "warning", "success" ];
+
+/// the built in themes
+pub const PRESETS : [&str; 4] = [ "default", "colorblind", "light", "mono" ];
+
+/// what a piece of text is, each role has its own style
+#[derive(Clone,Copy,PartialEq,Debug)]
+pub enum Role {
+    Key,
+    Value,
+    ValueSet,
+    Error,
+    Comment,
+    Heading,
+    Warning,
+    Success,
+}
+
+impl Role {
+    pub fn all() -> [Role; 8] {
+        [ Role::Key, Role::Value, Role::ValueSet, Role::Error, Role::Comment, Role::Heading, Role::Warning, Role::Success ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        ROLES[*self as usize]
+    }
+}
+
+impl FromStr for Role {
+    type Err = Error;
+
+    fn from_str(text : &str) -> Result<Role,Error> {
+        Role::all().iter()
+            .find(|role| role.name() == text)
+            .cloned()
+            .ok_or_else(|| ErrorKind::InvalidInput(format!("'{}' is not a theme role, use one of {}",text,ROLES.join(", "))).into())
+    }
+}
+
+/// the style for each role
+#[derive(Clone,PartialEq,Debug)]
+pub struct Theme {
+    styles : [Style; 8],
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            styles : [
+                Colour::Yellow.normal(), Colour::Blue.normal(), Colour::Green.normal(), Colour::Red.normal(),
+                Colour::Cyan.normal(), Colour::White.bold(), Colour::Purple.normal(), Colour::Green.normal() ],
+        }
+    }
+}
+
+impl Theme {
+    pub fn preset(name : &str) -> Result<Theme,Error> {
+        //! one of the built in themes, see `PRESETS`
+
+        let styles = match name {
+            "default" => return Ok(Theme::default()),
+            // avoids telling things apart by red and green
+            "colorblind" => [
+                Colour::Fixed(214).normal(), Colour::Fixed(39).normal(), Colour::Fixed(39).bold(), Colour::Fixed(202).bold(),
+                Colour::Fixed(245).normal(), Style::new().bold(), Colour::Fixed(214).bold(), Colour::Fixed(39).bold() ],
+            // darker colors for terminals with a light background
+            "light" => [
+                Colour::Fixed(130).normal(), Colour::Fixed(25).normal(), Colour::Fixed(28).normal(), Colour::Fixed(160).normal(),
+                Colour::Fixed(30).normal(), Colour::Black.bold(), Colour::Fixed(90).normal(), Colour::Fixed(28).normal() ],
+            "mono" => [
+                Style::new().bold(), Style::new(), Style::new().underline(), Style::new().bold(),
+                Style::new().dimmed(), Style::new().bold().underline(), Style::new().bold(), Style::new() ],
+            _ => return Err(ErrorKind::InvalidInput(format!("'{}' is not a theme, use one of {}",name,PRESETS.join(", "))).into()),
+        };
+
+        Ok(Theme { styles })
+    }
+
+    pub fn style(&self, role : Role) -> Style {
+        self.styles[role as usize]
+    }
+
+    pub fn set_style(&mut self, role : Role, style : Style) {
+        self.styles[role as usize] = style;
+    }
+
+    pub fn paint(&self, role : Role, text : &str) -> String {
+        //! the text in the role's style, plain if colors are off
+
+        self.paint_on(Stream::Stdout,role,text)
+    }
+
+    pub fn paint_on(&self, stream : Stream, role : Role, text : &str) -> String {
+        //! the text in the role's style, plain if colors are off for
+        //! the stream it is written to
+
+        if colors_enabled_on(stream) { format!("{}",self.style(role).paint(text)) }
+        else { text.to_string() }
+    }
+}
+
+pub fn parse_style(spec : &str) -> Result<Style,Error> {
+    //! reads a style like `yellow`, `bold red`, `underline 214` or
+    //! `#ff8800`. numbers are the 256 terminal colors, and `on` sets
+    //! the background, `black on yellow`. `plain` has no style.
+
+    let mut style = Style::new();
+    let mut background = false;
+
+    for word in spec.split_whitespace() {
+        let word = word.to_lowercase();
+        match word.as_str() {
+            "plain" | "none" => { },
+            "bold" => style = style.bold(),
+            "dimmed" | "dim" => style = style.dimmed(),
+            "italic" => style = style.italic(),
+            "underline" => style = style.underline(),
+            "reverse" => style = style.reverse(),
+            "on" => background = true,
+            color => {
+                let color = parse_color(color)
+                    .ok_or_else(|| ErrorKind::InvalidInput(format!("'{}' is not a color or style in '{}'",color,spec)))?;
+                if background { style = style.on(color); } else { style = style.fg(color); }
+            },
+        }
+    }
+
+    Ok(style)
+}
+
+fn parse_color(word : &str) -> Option<Colour> {
+    match word {
+        "black" => Some(Colour::Black),
+        "red" => Some(Colour::Red),
+        "green" => Some(Colour::Green),
+        "yellow" => Some(Colour::Yellow),
+        "blue" => Some(Colour::Blue),
+        "purple" | "magenta" => Some(Colour::Purple),
+        "cyan" => Some(Colour::Cyan),
+        "white" => Some(Colour::White),
+        hex if hex.starts_with('#') && hex.len() == 7 => {
+            let channel = |index : usize| u8::from_str_radix(&hex[index..index+2],16).ok();
+            Some(Colour::RGB(channel(1)?,channel(3)?,channel(5)?))
+        },
+        number => number.parse::<u8>().ok().map(Colour::Fixed),
+    }
+}
+
+// the theme in use, loaded from the settings the first time it is needed
+static THEME : RwLock<Option<Theme>> = RwLock::new(None);
+
+pub fn set_theme(theme : Theme) {
+    //! uses the theme instead of the one from the settings, for apps
+    //! that want their own
+
+    *THEME.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(theme);
+}
+
+pub fn get_theme() -> Theme {
+    //! the theme in use
+
+    if let Some(ref theme) = *THEME.read().unwrap_or_else(|poisoned| poisoned.into_inner()) {
+        return theme.clone();
+    }
+
+    let theme = load_theme();
+    set_theme(theme.clone());
+    theme
+}
+
+pub fn load_theme() -> Theme {
+    //! the theme from the settings. starts with the `lpsettings.theme.preset`
+    //! (or the `preset` in the theme file), then the roles in the theme
+    //! file, `lpsettings.theme.file` or `theme.toml` in the settings
+    //! folder, and then the `lpsettings.theme.ROLE` settings.
+    //!
+    //! a theme that can't be read is warned about and skipped, the
+    //! output shouldn't fail because of it.
+
+    let store = match Store::load() {
+        Ok(store) => store,
+        Err(error) => {
+            warn!("Cannot load the theme settings: {}",error);
+            return Theme::default();
+        },
+    };
+    let setting = |key : &str| -> Option<String> {
+        match store.get_value(key,&Scope::Best) {
+            Ok(Some(Type::Text(text))) => Some(text),
+            _ => None,
+        }
+    };
+
+    let file = load_theme_file(setting("lpsettings.theme.file"));
+    let preset = setting("lpsettings.theme.preset")
+        .or_else(|| file.get("preset").cloned())
+        .unwrap_or_else(|| "default".to_string());
+
+    let mut theme = Theme::preset(&preset).unwrap_or_else(|error| {
+        warn!("{}",error);
+        Theme::default()
+    });
+
+    for role in Role::all().iter() {
+        let spec = setting(&format!("lpsettings.theme.{}",role.name()))
+            .or_else(|| file.get(role.name()).cloned());
+        if let Some(spec) = spec {
+            match parse_style(&spec) {
+                Ok(style) => theme.set_style(*role,style),
+                Err(error) => warn!("Ignoring the theme's {}: {}",role.name(),error),
+            }
+        }
+    }
+
+    theme
+}
+
+fn load_theme_file(path : Option<String>) -> HashMap<String,String> {
+    //! the values in the theme file, empty if there isn't one
+
+    let path = match path {
+        Some(path) => get_folder().join(path),
+        None => get_folder().join("theme.toml"),
+    };
+    if !path.exists() { return HashMap::new(); }
+
+    let mut text = String::new();
+    let result = File::open(&path)
+        .and_then(|mut file| file.read_to_string(&mut text))
+        .map_err(Error::from)
+        .and_then(|_| toml::de::from_str::<HashMap<String,String>>(&text).map_err(Error::from));
+
+    result.unwrap_or_else(|error| {
+        warn!("Cannot read the theme file {}: {}",path.display(),error);
+        HashMap::new()
+    })
+}
+
+pub fn paint<T: AsRef<str>>(role : Role, text : T) -> String {
+    //! the text in the role's style from the theme in use
+
+    get_theme().paint(role,text.as_ref())
+}
 
 pub fn key<T: AsRef<str>>(text : T) -> String {
-    format!("{}",Colour::Yellow.paint(text.as_ref()))
+    paint(Role::Key,text)
 }
 
 pub fn key_value<T: AsRef<str>>(text : T) -> String {
-    format!("{}",Colour::Blue.paint(text.as_ref()))
+    paint(Role::Value,text)
 }
 
 pub fn key_value_set<T: AsRef<str>>(text : T) -> String {
-    format!("{}",Colour::Green.paint(text.as_ref()))
+    paint(Role::ValueSet,text)
 }
 
 pub fn error<T: AsRef<str>>(text : T) -> String {
-    format!("{}",Colour::Red.paint(text.as_ref()))
+    paint(Role::Error,text)
 }
 pub fn error_message<T: AsRef<str>>(text : T) -> String {
-    format!("{}",Colour::Red.paint(text.as_ref()))
+    paint(Role::Error,text)
 }
 
 pub fn comment<T: AsRef<str>>(text : T) -> String {
-    format!("{}",Colour::Cyan.paint(text.as_ref()))
+    paint(Role::Comment,text)
 }
 
 pub fn heading<T: AsRef<str>>(text : T) -> String {
-    format!("{}",Colour::White.bold().paint(text.as_ref()))
-}
\ No newline at end of file
+    paint(Role::Heading,text)
+}
+
+pub fn warning<T: AsRef<str>>(text : T) -> String {
+    paint(Role::Warning,text)
+}
+
+pub fn success<T: AsRef<str>>(text : T) -> String {
+    paint(Role::Success,text)
+}
@@ -0,0 +1,414 @@
+//! a full screen editor for the settings.
+//!
+//! the settings of one scope are shown as a tree, along with the
+//! keys from the options map that aren't set yet, and the selected
+//! key's description. changes are kept in memory until they are
+//! saved.
+//!
+//! ```text
+//! up / down    move              enter   edit the value, or fold a table
+//! tab          local / global    d       remove the value
+//! /            search            s       save
+//! q / esc      quit, asking to save if anything changed
+//! ```
+
+use std::collections::HashSet;
+
+use console::{ Term, Key, style };
+use failure::Error;
+use settingsfile::ShadowSettings;
+use toml;
+
+use settings::Configuration;
+use error::ErrorKind;
+use map::{ self, OptionsMap };
+use list;
+use value;
+use history;
+use secrets;
+use crypt;
+use theme;
+use { load_settings, save_settings, get_file_global, get_file_local };
+use Type;
+
+/// lines used by the header and the footer
+const CHROME : usize = 7;
+
+struct Row {
+    key : String,
+    depth : usize,
+    table : bool,
+}
+
+struct Editor {
+    term : Term,
+    settings : ShadowSettings<Configuration>,
+    secrets : ShadowSettings<Configuration>,
+    map : Vec<OptionsMap>,
+    local : bool,
+    rows : Vec<Row>,
+    selected : usize,
+    offset : usize,
+    folded : HashSet<String>,
+    search : Option<String>,
+    status : String,
+    changes : Vec<history::Change>,
+    secrets_changed : bool,
+}
+
+pub fn run(local : bool) -> Result<(),Error> {
+    //! opens the editor, starting in the local or global scope
+
+    let term = Term::stdout();
+    if !term.is_term() {
+        return Err(ErrorKind::InvalidInput("The editor needs to be run in a terminal".to_string()).into());
+    }
+
+    let mut editor = Editor {
+        term,
+        settings : load_settings(false)?,
+        secrets : load_settings(true)?,
+        map : map::create_options_map()?,
+        local,
+        rows : Vec::new(),
+        selected : 0,
+        offset : 0,
+        folded : HashSet::new(),
+        search : None,
+        status : String::new(),
+        changes : Vec::new(),
+        secrets_changed : false,
+    };
+
+    editor.term.hide_cursor()?;
+    let result = editor.run();
+    editor.term.show_cursor()?;
+    editor.term.clear_screen()?;
+    result
+}
+
+impl Editor {
+    fn run(&mut self) -> Result<(),Error> {
+        self.build_rows();
+
+        loop {
+            self.draw()?;
+
+            match self.term.read_key()? {
+                Key::ArrowUp | Key::Char('k') => self.move_by(-1),
+                Key::ArrowDown | Key::Char('j') => self.move_by(1),
+                Key::PageUp => self.move_by(-(self.list_height() as isize)),
+                Key::PageDown => self.move_by(self.list_height() as isize),
+                Key::Home => self.move_by(-(self.rows.len() as isize)),
+                Key::End => self.move_by(self.rows.len() as isize),
+                Key::Tab => {
+                    self.local = !self.local;
+                    self.selected = 0;
+                    self.build_rows();
+                },
+                Key::Enter => self.activate()?,
+                Key::Char('d') | Key::Del => self.remove()?,
+                Key::Char('/') => {
+                    let search = self.read_input("search: ",self.search.as_deref().unwrap_or(""))?;
+                    if let Some(search) = search {
+                        self.search = if search.is_empty() { None } else { Some(search) };
+                        self.selected = 0;
+                        self.build_rows();
+                    }
+                },
+                Key::Char('s') => self.save()?,
+                Key::Char('q') | Key::Escape if self.confirm_quit()? => return Ok(()),
+                _ => { },
+            }
+        }
+    }
+
+    fn scope(&self) -> &'static str {
+        if self.local { "local" } else { "global" }
+    }
+
+    fn list_height(&self) -> usize {
+        let (height, _) = self.term.size();
+        (height as usize).saturating_sub(CHROME).max(1)
+    }
+
+    fn get_value(&self, key : &str) -> Option<Type> {
+        let stores = [&self.secrets, &self.settings];
+        stores.iter()
+            .filter_map(|store| if self.local { store.get_value_local(key) } else { store.get_value_global(key) })
+            .next()
+    }
+
+    fn build_rows(&mut self) {
+        //! the rows of the tree, the keys in the scope and the valid
+        //! keys from the options map, without anything in a folded table.
+
+        let mut keys : Vec<String> = Vec::new();
+        for store in [&self.settings, &self.secrets].iter() {
+            keys.append(&mut if self.local { store.keys_local() } else { store.keys_global() });
+        }
+        for m in self.map.iter().filter(|m| map::is_current(m)) {
+            keys.push(m.key.to_string());
+        }
+        if let Some(ref search) = self.search {
+            keys.retain(|key| key.contains(search.as_str()) || list::matches(search,key));
+        }
+        keys.sort();
+        keys.dedup();
+
+        let mut rows : Vec<Row> = Vec::new();
+        let mut tables : HashSet<String> = HashSet::new();
+
+        'keys: for key in keys {
+            let parts : Vec<&str> = key.split('.').collect();
+            for depth in 0..parts.len() - 1 {
+                let table = parts[..depth+1].join(".");
+                if !tables.contains(&table) {
+                    tables.insert(table.to_string());
+                    rows.push(Row { key : table.to_string(), depth, table : true });
+                }
+                // nothing inside of a folded table is shown
+                if self.folded.contains(&table) { continue 'keys; }
+            }
+            rows.push(Row { key : key.to_string(), depth : parts.len() - 1, table : false });
+        }
+
+        self.rows = rows;
+        if self.selected >= self.rows.len() {
+            self.selected = self.rows.len().saturating_sub(1);
+        }
+    }
+
+    fn move_by(&mut self, amount : isize) {
+        if self.rows.is_empty() { return; }
+        let selected = self.selected as isize + amount;
+        self.selected = selected.max(0).min(self.rows.len() as isize - 1) as usize;
+    }
+
+    fn draw(&mut self) -> Result<(),Error> {
+        let height = self.list_height();
+        if self.selected < self.offset { self.offset = self.selected; }
+        if self.selected >= self.offset + height { self.offset = self.selected + 1 - height; }
+
+        let mut lines : Vec<String> = Vec::new();
+        lines.push(format!("{} {}",
+            theme::heading(format!("lpsettings - {} settings",self.scope())),
+            theme::comment(if self.changes.is_empty() { "" } else { "(modified)" })
+        ));
+
+        for (index,row) in self.rows.iter().enumerate().skip(self.offset).take(height) {
+            let name = row.key.rsplit('.').next().unwrap_or("");
+            let indent = "  ".repeat(row.depth);
+
+            let line = if row.table {
+                let marker = if self.folded.contains(&row.key) { "+" } else { "-" };
+                if index == self.selected {
+                    format!("{}{} {}",indent,marker,style(name).reverse())
+                } else {
+                    format!("{}{} {}",indent,marker,theme::key(name))
+                }
+            } else {
+                let value = self.display_value(&row.key);
+                if index == self.selected {
+                    format!("{}  {}",indent,style(format!("{}: {}",name,value)).reverse())
+                } else {
+                    format!("{}  {}: {}",indent,theme::key(name),theme::key_value(value))
+                }
+            };
+            lines.push(line);
+        }
+        while lines.len() < height + 1 { lines.push(String::new()); }
+
+        // what is known about the selected key
+        lines.push(theme::comment("-".repeat(40)));
+        match self.rows.get(self.selected) {
+            Some(row) if !row.table => {
+                let kind = map::get_kind(&self.map,&row.key)
+                    .or_else(|| self.get_value(&row.key).as_ref().and_then(value::type_of))
+                    .unwrap_or("any");
+                lines.push(format!("{} ({})",theme::key(&row.key),kind));
+                lines.push(theme::comment(map::get_desc(&self.map,&row.key).unwrap_or("")));
+            },
+            Some(row) => {
+                lines.push(theme::key(&row.key));
+                lines.push(String::new());
+            },
+            None => {
+                lines.push(theme::comment("nothing to show"));
+                lines.push(String::new());
+            },
+        }
+        lines.push(self.status.to_string());
+        lines.push(theme::comment("enter edit  d remove  tab local/global  / search  s save  q quit"));
+
+        self.term.clear_screen()?;
+        self.term.write_str(&lines.join("\n"))?;
+        self.term.flush()?;
+        Ok(())
+    }
+
+    fn display_value(&self, key : &str) -> String {
+        match self.get_value(key) {
+            None => "[not set]".to_string(),
+            Some(ref value) if crypt::is_encrypted(value) => "[encrypted]".to_string(),
+            Some(value) => format!("{}",secrets::mask(key,value)),
+        }
+    }
+
+    fn activate(&mut self) -> Result<(),Error> {
+        //! folds the table, or edits the key
+
+        let (key, table) = match self.rows.get(self.selected) {
+            Some(row) => (row.key.to_string(), row.table),
+            None => return Ok(()),
+        };
+
+        if table {
+            if !self.folded.remove(&key) { self.folded.insert(key); }
+            self.build_rows();
+            return Ok(());
+        }
+
+        let current = self.get_value(&key);
+        let encrypted = current.as_ref().map(crypt::is_encrypted).unwrap_or(false);
+        let choices = map::get_choices(&self.map,&key).to_vec();
+
+        // secret and encrypted values aren't shown, so they start empty
+        let mut input = match current {
+            Some(ref value) if !encrypted && !secrets::is_secret(&key) => to_input(value),
+            _ => String::new(),
+        };
+
+        loop {
+            input = match self.read_input(&format!("{} = ",key),&input)? {
+                None => { self.status.clear(); return Ok(()); },
+                Some(input) => input,
+            };
+
+            let parsed = value::parse_for(&self.map,&key,current.as_ref(),&input);
+
+            match parsed {
+                Err(error) => self.status = theme::error(error.to_string()),
+                Ok(_) if !choices.is_empty() && !choices.contains(&input) => {
+                    self.status = theme::error(format!("'{}' is not one of {}",input,choices.join(", ")));
+                },
+                Ok(new_value) => {
+                    let new_value = if encrypted { crypt::encrypt(&new_value)? } else { new_value };
+                    self.set(&key,Some(new_value))?;
+                    self.status = format!("Set {}",theme::key(&key));
+                    return Ok(());
+                }
+            }
+            self.draw()?;
+        }
+    }
+
+    fn remove(&mut self) -> Result<(),Error> {
+        let key = match self.rows.get(self.selected) {
+            Some(row) if !row.table => row.key.to_string(),
+            _ => return Ok(()),
+        };
+
+        if self.get_value(&key).is_none() { return Ok(()); }
+        self.set(&key,None)?;
+        self.status = format!("Removed {}",theme::key(&key));
+        Ok(())
+    }
+
+    fn set(&mut self, key : &str, new_value : Option<Type>) -> Result<(),Error> {
+        //! changes the value in memory, recording the change for the history
+
+        let secret = secrets::is_secret(key);
+        let local = self.local;
+        let store = if secret { &mut self.secrets } else { &mut self.settings };
+
+        let old_value = match (&new_value, local) {
+            (Some(value), true) => { let old = store.get_value_local(key); store.set_value_local(key,value)?; old },
+            (Some(value), false) => { let old = store.get_value_global(key); store.set_value_global(key,value)?; old },
+            (None, true) => store.delete_key_local(key),
+            (None, false) => store.delete_key_global(key),
+        };
+
+        let file = if local { get_file_local(secret) } else { get_file_global(secret) };
+        let action = if new_value.is_some() { "tui" } else { "tui-unset" };
+        self.changes.push(history::Change::new(action,self.scope(),&file,key,old_value,new_value));
+        self.secrets_changed = self.secrets_changed || secret;
+
+        self.build_rows();
+        Ok(())
+    }
+
+    fn save(&mut self) -> Result<(),Error> {
+        if self.changes.is_empty() {
+            self.status = "Nothing to save".to_string();
+            return Ok(());
+        }
+
+        save_settings(&self.settings,false)?;
+        if self.secrets_changed {
+            save_settings(&self.secrets,true)?;
+        }
+        history::record(&self.changes);
+
+        self.status = format!("Saved {} changes",self.changes.len());
+        self.changes.clear();
+        self.secrets_changed = false;
+        Ok(())
+    }
+
+    fn confirm_quit(&mut self) -> Result<bool,Error> {
+        //! asks to save if anything changed, `false` if the user
+        //! wants to keep editing.
+
+        if self.changes.is_empty() { return Ok(true); }
+
+        self.status = "Save changes? (y)es, (n)o, (c)ancel".to_string();
+        loop {
+            self.draw()?;
+            match self.term.read_key()? {
+                Key::Char('y') => { self.save()?; return Ok(true); },
+                Key::Char('n') => return Ok(true),
+                Key::Char('c') | Key::Escape => { self.status.clear(); return Ok(false); },
+                _ => { },
+            }
+        }
+    }
+
+    fn read_input(&self, prompt : &str, initial : &str) -> Result<Option<String>,Error> {
+        //! reads a line on the last line of the screen, `None` if it
+        //! was cancelled with escape.
+
+        let mut input : String = initial.to_string();
+        let (height, _) = self.term.size();
+
+        self.term.show_cursor()?;
+        let result = loop {
+            self.term.move_cursor_to(0,height as usize - 1)?;
+            self.term.clear_line()?;
+            self.term.write_str(&format!("{}{}",theme::key(prompt),input))?;
+            self.term.flush()?;
+
+            match self.term.read_key()? {
+                Key::Enter => break Some(input),
+                Key::Escape => break None,
+                Key::Backspace => { input.pop(); },
+                Key::Char(chr) if !chr.is_control() => input.push(chr),
+                _ => { },
+            }
+        };
+        self.term.hide_cursor()?;
+
+        Ok(result)
+    }
+}
+
+fn to_input(value : &Type) -> String {
+    //! the value as it would be typed in
+
+    match value {
+        Type::Text(text) => text.to_string(),
+        value => toml::Value::try_from(value)
+            .map(|value| value.to_string())
+            .unwrap_or_else(|_| format!("{}",value)),
+    }
+}
@@ -1,4 +1,4 @@
-/// useful functions for updating?
+//! useful functions for updating?
 
 use failure::Error;
 use chrono;
@@ -23,14 +23,12 @@ pub fn check_if_should_update(app_name : &str) -> bool {
     let now = chrono::Utc::now();
 
     // checks if should update based on the frequency
-    if let Ok(Some(last_check)) = get_value(&format!("{}.{}",app_name,"update.last_check")) {
-        if let Type::Text(text_date) = last_check {
-            match chrono::DateTime::parse_from_rfc3339(&text_date){
-                Err(error) => { error!("{}",error); return false; },
-                Ok(date) => {
-                    if now.signed_duration_since(date).num_days() < frequency { return false; }
-                },
-            }
+    if let Ok(Some(Type::Text(text_date))) = get_value(&format!("{}.{}",app_name,"update.last_check")) {
+        match chrono::DateTime::parse_from_rfc3339(&text_date){
+            Err(error) => { error!("{}",error); return false; },
+            Ok(date) => {
+                if now.signed_duration_since(date).num_days() < frequency { return false; }
+            },
         }
     }
 
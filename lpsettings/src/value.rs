@@ -0,0 +1,105 @@
+//! reading values that are typed in, on the command line, in the
+//! environment or an editor, into a `Type`.
+
+use failure::Error;
+use toml;
+
+use error::ErrorKind;
+use map::{ self, OptionsMap };
+use crypt;
+use Type;
+
+/// the names of the types, as used in the options map
+pub const TYPES : [&str; 5] = [ "text", "switch", "int", "float", "array" ];
+
+pub fn parse(input : &str) -> Type {
+    //! reads the value as toml (`true`, `3`, `[ "a", "b" ]`), using
+    //! it as text if it isn't valid
+
+    match toml::de::from_str::<toml::Value>(&format!("value = {}",input)) {
+        Ok(toml::Value::Table(mut table)) => {
+            match table.remove("value").map(|value| value.try_into::<Type>()) {
+                Some(Ok(value)) => value,
+                _ => Type::Text(input.to_string()),
+            }
+        },
+        _ => Type::Text(input.to_string()),
+    }
+}
+
+pub fn parse_as(kind : &str, input : &str) -> Result<Type,Error> {
+    //! reads the value as the type, erroring if it isn't that type.
+    //! an `int` is accepted as a `float`, a single text value as an
+    //! `array` (one link instead of a list of them) and anything is
+    //! accepted as `text`.
+
+    let invalid = || -> Error {
+        ErrorKind::InvalidInput(format!("'{}' is not a valid {}",input,kind)).into()
+    };
+
+    match (kind, parse(input)) {
+        ("text", _) => Ok(Type::Text(input.to_string())),
+        ("switch", Type::Switch(switch)) => Ok(Type::Switch(switch)),
+        ("int", Type::Int(int)) => Ok(Type::Int(int)),
+        ("float", Type::Float(float)) => Ok(Type::Float(float)),
+        ("float", Type::Int(int)) => Ok(Type::Float(int as f32)),
+        ("array", Type::Array(array)) => Ok(Type::Array(array)),
+        ("array", Type::Text(_)) => Ok(Type::Text(input.to_string())),
+        (kind, _) if !TYPES.contains(&kind) => Err(format_err!("'{}' is not a known type, use one of {}",kind,TYPES.join(", "))),
+        _ => Err(invalid()),
+    }
+}
+
+pub fn parse_for(map : &[OptionsMap], key : &str, current : Option<&Type>, input : &str) -> Result<Type,Error> {
+    //! reads the value as the key's type in the options map, or as the
+    //! type of its current value. anything else is kept as text, so
+    //! `1.10` isn't turned into a float.
+
+    let kind = map::get_kind(map,key).or_else(|| current.and_then(type_of));
+    parse_as(kind.unwrap_or("text"),input)
+}
+
+pub fn type_of(value : &Type) -> Option<&'static str> {
+    //! the type name of the value, if it is one that can be typed in
+
+    match value {
+        Type::Text(_) => Some("text"),
+        Type::Switch(_) => Some("switch"),
+        Type::Int(_) => Some("int"),
+        Type::Float(_) => Some("float"),
+        Type::Array(_) => Some("array"),
+        Type::Complex(_) | Type::None => None,
+    }
+}
+
+pub fn check(map : &[OptionsMap], key : &str, value : &Type) -> Result<(),Error> {
+    //! checks the value against the type and choices for the key in
+    //! the options map. encrypted values can't be checked.
+
+    if crypt::is_encrypted(value) { return Ok(()); }
+
+    if let Some(kind) = map::get_kind(map,key) {
+        let valid = matches!((kind, value),
+            ("text", Type::Text(_)) |
+            ("switch", Type::Switch(_)) |
+            ("int", Type::Int(_)) |
+            ("float", Type::Float(_)) | ("float", Type::Int(_)) |
+            ("array", Type::Array(_)) | ("array", Type::Text(_)));
+        if !valid {
+            return Err(ErrorKind::InvalidInput(format!("'{}' should be {} {}, not {}",
+                key,article(kind),kind,type_of(value).unwrap_or("a table"))).into());
+        }
+    }
+
+    let choices = map::get_choices(map,key);
+    if !choices.is_empty() && !choices.contains(&format!("{}",value)) {
+        return Err(ErrorKind::InvalidInput(format!("'{}' should be one of {}, not {}",
+            key,choices.join(", "),value)).into());
+    }
+
+    Ok(())
+}
+
+fn article(kind : &str) -> &'static str {
+    if kind.starts_with(|c : char| "aeiou".contains(c)) { "an" } else { "a" }
+}
@@ -0,0 +1,65 @@
+//! backups of the settings files, and comparing against them.
+
+extern crate lpsettings;
+
+mod common;
+
+use std::env;
+use std::fs;
+
+use lpsettings::{ backup, diff };
+use lpsettings::diff::Kind;
+
+use common::Sandbox;
+
+#[test]
+fn backups_never_replace_each_other() {
+    let _sandbox = Sandbox::new("backup-unique");
+
+    lpsettings::set_value("user.name",&"ada").unwrap();
+    let first = backup::create(false).unwrap();
+    let second = backup::create(false).unwrap();
+
+    assert_ne!(first,second);
+    assert_eq!(backup::list().unwrap(),vec![first,second.clone()]);
+    assert_eq!(backup::find("latest").unwrap(),second);
+}
+
+#[test]
+fn local_backups_are_kept_per_project() {
+    let sandbox = Sandbox::new("backup-projects");
+
+    let game = sandbox.path.join("project").join("game");
+    let tool = sandbox.path.join("project").join("tool");
+    for folder in [&game, &tool].iter() {
+        fs::create_dir_all(folder.join(".git")).unwrap();
+        fs::write(folder.join("lovepack.toml"),"[project]\nname = \"x\"\n").unwrap();
+    }
+
+    env::set_current_dir(&game).unwrap();
+    let path = backup::create(true).unwrap();
+    assert_eq!(backup::list().unwrap(),vec![path]);
+    assert!(diff::backup("latest").unwrap().is_empty());
+
+    env::set_current_dir(&tool).unwrap();
+    assert!(backup::list().unwrap().is_empty());
+    assert!(diff::backup("latest").is_err());
+}
+
+#[test]
+fn the_scopes_diff_only_has_what_the_local_settings_change() {
+    let _sandbox = Sandbox::new("backup-diff-scopes");
+
+    lpsettings::set_value("user.name",&"ada").unwrap();
+    lpsettings::set_value("user.email",&"ada@example.com").unwrap();
+    lpsettings::set_value_local("user.name",&"grace").unwrap();
+    lpsettings::set_value_local("project.name",&"game").unwrap();
+
+    let changes : Vec<(String,Kind)> = diff::scopes().unwrap().into_iter()
+        .map(|change| (change.key,change.kind))
+        .collect();
+    assert_eq!(changes,vec![
+        ("project.name".to_string(),Kind::Added),
+        ("user.name".to_string(),Kind::Changed),
+    ]);
+}
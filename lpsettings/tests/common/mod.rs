@@ -0,0 +1,53 @@
+//! a temporary home, project and system folder for the tests that
+//! read and write the settings files.
+
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::sync::{ Mutex, MutexGuard };
+
+/// the settings are found through `HOME` and the current directory,
+/// which are shared by the whole process, so only one test can use
+/// them at a time.
+static LOCK : Mutex<()> = Mutex::new(());
+
+pub struct Sandbox {
+    pub path : PathBuf,
+    _guard : MutexGuard<'static,()>,
+}
+
+impl Sandbox {
+    pub fn new(name : &str) -> Sandbox {
+        let guard = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let mut path = env::temp_dir();
+        path.push(format!("lpsettings-test-{}-{}",process::id(),name));
+        fs::remove_dir_all(&path).ok();
+        fs::create_dir_all(path.join("home")).unwrap();
+        fs::create_dir_all(path.join("project")).unwrap();
+
+        env::set_var("HOME",path.join("home"));
+        env::set_var("LOVEPACK_SYSTEM_DIR",path.join("system"));
+        env::set_current_dir(path.join("project")).unwrap();
+
+        Sandbox { path, _guard : guard }
+    }
+
+    pub fn local_file(&self) -> PathBuf {
+        self.path.join("project").join("lovepack.toml")
+    }
+
+    pub fn system_file(&self) -> PathBuf {
+        self.path.join("system").join("lovepack.toml")
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        env::set_current_dir(env::temp_dir()).ok();
+        fs::remove_dir_all(&self.path).ok();
+    }
+}
@@ -0,0 +1,100 @@
+//! the completion scripts, which add to what clap generates.
+
+#![cfg(feature = "cli")]
+
+extern crate lpsettings;
+
+mod common;
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::{ Command, Stdio };
+
+use lpsettings::completion;
+use lpsettings::interface;
+
+use common::Sandbox;
+
+fn generate(shell : &str) -> String {
+    let mut script : Vec<u8> = Vec::new();
+    completion::generate(&mut interface::app(),shell,&mut script).unwrap();
+    String::from_utf8(script).unwrap()
+}
+
+fn check_syntax(shell : &str, script : &str) {
+    //! parses the script without running it, when the shell is installed
+
+    let mut child = match Command::new(shell).arg("-n")
+        .stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::piped())
+        .spawn() {
+        Ok(child) => child,
+        Err(_) => { println!("{} isn't installed, not checking the syntax",shell); return; },
+    };
+
+    child.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(),"{} -n failed: {}",shell,String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn bash_script() {
+    let script = generate("bash");
+
+    assert!(script.contains("_lpsettings_dynamic() {"));
+    assert!(script.contains("1:unset|1:history|1:list)"));
+    // the values of options aren't taken as the key
+    assert!(script.contains("--format|--profile|--backup|--color|--from|--set) skip=1 ;;"));
+    assert!(script.trim_end().ends_with("complete -F _lpsettings_dynamic -o bashdefault -o default lpsettings"));
+    check_syntax("bash",&script);
+}
+
+#[test]
+fn zsh_script() {
+    let script = generate("zsh");
+
+    // the arguments clap completes as files ask for keys and values
+    assert!(script.lines().any(|line| line.starts_with("'::KEY") && line.ends_with(":_lpsettings_keys' \\")));
+    assert!(script.lines().any(|line| line.starts_with("'::FILTER") && line.ends_with(":_lpsettings_keys' \\")));
+    assert!(script.lines().any(|line| line.starts_with("'::VALUE") && line.ends_with(":_lpsettings_values' \\")));
+    assert!(!script.lines().any(|line| (line.contains(":KEY") || line.contains(":VALUE")) && line.ends_with(":_files' \\")));
+
+    // and the helpers are defined before the script calls itself
+    let helpers = script.find("_lpsettings_keys() {").expect("the helpers are missing");
+    assert!(helpers < script.rfind("_lpsettings \"$@\"").unwrap());
+    check_syntax("zsh",&script);
+}
+
+#[test]
+fn fish_script() {
+    let script = generate("fish");
+
+    assert!(script.contains("function __fish_lpsettings_args"));
+    assert!(script.contains("contains -- $args[1] unset history list"));
+    assert!(script.contains("case --format --profile --backup --color --from --set\n"));
+    check_syntax("fish",&script);
+}
+
+#[test]
+fn unknown_shells_are_an_error() {
+    let mut script : Vec<u8> = Vec::new();
+    assert!(completion::generate(&mut interface::app(),"tcsh",&mut script).is_err());
+}
+
+#[test]
+fn keys_are_read_from_every_settings_file() {
+    let sandbox = Sandbox::new("completion-keys");
+
+    fs::create_dir_all(sandbox.system_file().parent().unwrap()).unwrap();
+    fs::write(sandbox.system_file(),"[lab]\nname = \"lab\"\n").unwrap();
+    let inner = sandbox.path.join("project").join("game");
+    fs::create_dir_all(&inner).unwrap();
+    fs::write(sandbox.local_file(),"[shared]\nname = \"games\"\n").unwrap();
+    fs::write(inner.join("lovepack.toml"),"[project]\nname = \"game\"\n").unwrap();
+    env::set_current_dir(&inner).unwrap();
+
+    let keys = completion::keys().unwrap();
+    assert!(keys.contains(&"lab.name".to_string()));
+    assert!(keys.contains(&"shared.name".to_string()));
+    assert!(keys.contains(&"user.name".to_string()));
+}
@@ -0,0 +1,66 @@
+//! encrypting values with the local key file.
+
+extern crate lpsettings;
+
+mod common;
+
+use std::env;
+use std::fs;
+use std::collections::HashMap;
+
+use lpsettings::Type;
+use lpsettings::crypt;
+
+use common::Sandbox;
+
+fn text(value : &str) -> Type {
+    Type::Text(value.to_string())
+}
+
+#[test]
+fn values_round_trip() {
+    let sandbox = Sandbox::new("crypt-round-trip");
+    env::set_var("LOVEPACK_KEY_FILE",sandbox.path.join("lovepack.key"));
+
+    let mut table = HashMap::new();
+    table.insert("name".to_string(),text("ada"));
+    table.insert("count".to_string(),Type::Int(3));
+    let value = Type::Complex(table);
+
+    let encrypted = crypt::encrypt(&value).unwrap();
+    assert!(crypt::is_encrypted(&encrypted));
+    assert_eq!(crypt::decrypt(encrypted).unwrap(),value);
+
+    // stored encrypted, read back decrypted
+    lpsettings::set_value_encrypted("user.name",&"ada").unwrap();
+    let stored = fs::read_to_string(sandbox.path.join("home").join(".lovepack").join("lovepack.toml")).unwrap();
+    assert!(stored.contains("enc:v1:") && !stored.contains("ada"));
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),Some(text("ada")));
+
+    env::remove_var("LOVEPACK_KEY_FILE");
+}
+
+#[test]
+fn a_wrong_or_missing_key_only_fails_reading() {
+    let sandbox = Sandbox::new("crypt-key");
+    let key_file = sandbox.path.join("lovepack.key");
+    env::set_var("LOVEPACK_KEY_FILE",&key_file);
+
+    lpsettings::set_value_encrypted("user.name",&"ada").unwrap();
+
+    // a different key
+    fs::write(&key_file,[7u8; 32]).unwrap();
+    assert!(lpsettings::get_value("user.name").is_err());
+
+    // no key at all
+    fs::remove_file(&key_file).unwrap();
+    assert!(lpsettings::get_value("user.name").is_err());
+
+    // the value can still be replaced, the old one is returned as stored
+    let old = lpsettings::set_value("user.name",&"grace").unwrap().unwrap();
+    assert!(crypt::is_encrypted(&old));
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),Some(text("grace")));
+    assert!(!key_file.exists());
+
+    env::remove_var("LOVEPACK_KEY_FILE");
+}
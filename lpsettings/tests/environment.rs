@@ -0,0 +1,63 @@
+//! overriding settings with environmental variables, and the defaults
+//! from the options map.
+
+extern crate lpsettings;
+
+mod common;
+
+use std::env;
+
+use lpsettings::{ Type, Scope };
+
+use common::Sandbox;
+
+#[test]
+fn variables_override_every_best_read() {
+    let _sandbox = Sandbox::new("environment-override");
+
+    lpsettings::set_value("user.name",&"ada").unwrap();
+    lpsettings::set_value_local("user.name",&"grace").unwrap();
+    env::set_var("LOVEPACK_USER__NAME","bob");
+    env::set_var("LOVEPACK_LPRUN__REPO__UPDATE__FREQ","3");
+    env::set_var("LOVEPACK_GAME__VERSION","1.10");
+
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),Some(Type::Text("bob".to_string())));
+    assert_eq!(lpsettings::get_value_in("user.name",Scope::Best).unwrap(),Some(Type::Text("bob".to_string())));
+    assert_eq!(lpsettings::get_value("lprun.repo.update.freq").unwrap(),Some(Type::Int(3)));
+    // keys without a type in the map are text
+    assert_eq!(lpsettings::get_value("game.version").unwrap(),Some(Type::Text("1.10".to_string())));
+
+    // a single scope is read as it is
+    assert_eq!(lpsettings::get_value_global("user.name").unwrap(),Some(Type::Text("ada".to_string())));
+    assert_eq!(lpsettings::get_value_local("user.name").unwrap(),Some(Type::Text("grace".to_string())));
+
+    env::remove_var("LOVEPACK_USER__NAME");
+    env::remove_var("LOVEPACK_LPRUN__REPO__UPDATE__FREQ");
+    env::remove_var("LOVEPACK_GAME__VERSION");
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),Some(Type::Text("grace".to_string())));
+}
+
+#[test]
+fn lovepacks_own_variables_are_not_keys() {
+    let _sandbox = Sandbox::new("environment-reserved");
+
+    // the sandbox sets LOVEPACK_SYSTEM_DIR
+    assert!(env::var("LOVEPACK_SYSTEM_DIR").is_ok());
+    assert_eq!(lpsettings::get_value("system_dir").unwrap(),None);
+
+    env::set_var(lpsettings::crypt::ENV_VAR,"lovepack.key");
+    let value = lpsettings::get_value("key_file").unwrap();
+    env::remove_var(lpsettings::crypt::ENV_VAR);
+    assert_eq!(value,None);
+}
+
+#[test]
+fn defaults_are_used_when_nothing_is_set() {
+    let _sandbox = Sandbox::new("environment-defaults");
+
+    assert_eq!(lpsettings::get_value("lprun.repo.use_defaults").unwrap(),Some(Type::Switch(true)));
+    assert_eq!(lpsettings::get_value_global("lprun.repo.use_defaults").unwrap(),None);
+
+    lpsettings::set_value("lprun.repo.use_defaults",&false).unwrap();
+    assert_eq!(lpsettings::get_value("lprun.repo.use_defaults").unwrap(),Some(Type::Switch(false)));
+}
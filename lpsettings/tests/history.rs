@@ -0,0 +1,72 @@
+//! the change journal, and undoing whole commands from it.
+
+extern crate lpsettings;
+
+mod common;
+
+use std::fs;
+
+use lpsettings::Type;
+use lpsettings::history;
+
+use common::Sandbox;
+
+fn text(value : &str) -> Option<Type> {
+    Some(Type::Text(value.to_string()))
+}
+
+#[test]
+fn changes_are_recorded() {
+    let _sandbox = Sandbox::new("history-record");
+
+    lpsettings::set_value("user.name",&"ada").unwrap();
+    lpsettings::set_value("user.name",&"grace").unwrap();
+    assert_eq!(lpsettings::unset_value("user.name").unwrap(),text("grace"));
+    // nothing was removed, so nothing is recorded
+    assert_eq!(lpsettings::unset_value("user.name").unwrap(),None);
+
+    let changes = history::get_changes(Some("user")).unwrap();
+    let actions : Vec<&str> = changes.iter().map(|c| c.action.as_str()).collect();
+    assert_eq!(actions,vec!["set","set","unset"]);
+    assert_eq!(changes[1].old,text("ada"));
+    assert_eq!(changes[1].new,text("grace"));
+    assert_eq!(changes[2].new,None);
+    assert!(history::get_changes(Some("project")).unwrap().is_empty());
+}
+
+#[test]
+fn undo_reverts_the_last_command() {
+    let _sandbox = Sandbox::new("history-undo");
+
+    lpsettings::set_value("user.name",&"ada").unwrap();
+    lpsettings::set_value("user.name",&"grace").unwrap();
+    lpsettings::unset_value("user.name").unwrap();
+
+    history::undo(1).unwrap();
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),text("grace"));
+
+    history::undo(2).unwrap();
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),None);
+    assert!(history::get_changes(None).unwrap().is_empty());
+    assert!(history::undo(1).unwrap().is_empty());
+}
+
+#[test]
+fn undo_reverts_all_of_an_import() {
+    let sandbox = Sandbox::new("history-import");
+
+    lpsettings::set_value("user.name",&"ada").unwrap();
+
+    let path = sandbox.path.join("import.toml");
+    fs::write(&path,"[user]\nname = \"grace\"\nemail = \"grace@example.com\"\n").unwrap();
+    let mut keys = lpsettings::import(&path).unwrap();
+    keys.sort();
+    assert_eq!(keys,vec!["user.email","user.name"]);
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),text("grace"));
+
+    // one undo is the whole import, not one of its keys
+    assert_eq!(history::undo(1).unwrap().len(),2);
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),text("ada"));
+    assert_eq!(lpsettings::get_value("user.email").unwrap(),None);
+    assert_eq!(history::get_changes(None).unwrap().len(),1);
+}
@@ -0,0 +1,226 @@
+//! drives `init` with scripted answers against settings in a temporary
+//! home and project folder.
+
+extern crate lpsettings;
+extern crate failure;
+
+mod common;
+
+use std::env;
+use std::fs;
+
+use failure::Error;
+
+use lpsettings::Type;
+use lpsettings::init::{ self, Options };
+use lpsettings::prompt::{ Prompter, Scripted, Recording };
+
+use common::Sandbox;
+
+/// a prompter that nobody is at, like stdin from a pipe
+struct Closed;
+
+impl Prompter for Closed {
+    fn ask(&mut self, question : &str) -> Result<String,Error> {
+        panic!("asked '{}' without anyone to answer",question);
+    }
+
+    fn is_interactive(&self) -> bool { false }
+}
+
+fn text(value : &str) -> Option<Type> {
+    Some(Type::Text(value.to_string()))
+}
+
+#[test]
+fn asks_for_each_init_key() {
+    let _sandbox = Sandbox::new("asks");
+
+    let mut prompter = Recording::new(Scripted::new(&["ada", "ada@example.com", "y"]));
+    let written = init::run(&Options::new(),&mut prompter).unwrap();
+
+    let questions = prompter.questions();
+    assert_eq!(questions.len(),3);
+    assert!(questions[0].contains("user.name"));
+    assert!(questions[1].contains("user.email"));
+
+    assert_eq!(written.len(),2);
+    assert_eq!(lpsettings::get_value_global("user.name").unwrap(),text("ada"));
+    assert_eq!(lpsettings::get_value_global("user.email").unwrap(),text("ada@example.com"));
+}
+
+#[test]
+fn blank_answers_keep_the_existing_values() {
+    let _sandbox = Sandbox::new("blank");
+
+    init::run(&Options::new(),&mut Scripted::new(&["ada", "ada@example.com", ""])).unwrap();
+
+    let mut prompter = Recording::new(Scripted::new(&["", "bob@example.com", ""]));
+    let written = init::run(&Options::new(),&mut prompter).unwrap();
+
+    // the current value is shown in the question
+    assert!(prompter.questions()[0].contains("ada"));
+
+    assert_eq!(written,vec![("user.email".to_string(),Type::Text("bob@example.com".to_string()))]);
+    assert_eq!(lpsettings::get_value_global("user.name").unwrap(),text("ada"));
+    assert_eq!(lpsettings::get_value_global("user.email").unwrap(),text("bob@example.com"));
+}
+
+#[test]
+fn running_out_of_answers_is_an_error() {
+    let _sandbox = Sandbox::new("runs-out");
+
+    let mut prompter = Scripted::new(&["ada"]);
+    assert!(init::run(&Options::new(),&mut prompter).is_err());
+    assert_eq!(prompter.remaining(),0);
+
+    // nothing is saved when it fails part way
+    assert_eq!(lpsettings::get_value_global("user.name").unwrap(),None);
+}
+
+#[test]
+fn fails_without_a_terminal_or_answers() {
+    let _sandbox = Sandbox::new("no-terminal");
+
+    let error = init::run(&Options::new(),&mut Closed).unwrap_err();
+    assert_eq!(lpsettings::error::exit_code(&error),lpsettings::error::code::INVALID_INPUT);
+}
+
+#[test]
+fn answers_given_up_front_are_not_asked() {
+    let sandbox = Sandbox::new("up-front");
+
+    let answers = sandbox.path.join("answers.toml");
+    fs::write(&answers,"[user]\nname = \"ada\"\n").unwrap();
+
+    let options = Options::new()
+        .yes(true)
+        .from(&answers)
+        .set("user.email","ada@example.com")
+        .set("lprun.repo.update.freq","4");
+    let written = init::run(&options,&mut Closed).unwrap();
+
+    assert_eq!(written.len(),3);
+    assert_eq!(lpsettings::get_value_global("user.name").unwrap(),text("ada"));
+    assert_eq!(lpsettings::get_value_global("lprun.repo.update.freq").unwrap(),Some(Type::Int(4)));
+}
+
+#[test]
+fn answers_are_checked_against_the_map() {
+    let _sandbox = Sandbox::new("checked");
+
+    let options = Options::new().set("lprun.repo.update.freq","often");
+    assert!(init::run(&options,&mut Closed).is_err());
+    assert_eq!(lpsettings::get_value_global("lprun.repo.update.freq").unwrap(),None);
+
+    // keys the map doesn't have a type for are kept as text
+    init::run(&Options::new().set("game.version","1.10"),&mut Closed).unwrap();
+    assert_eq!(lpsettings::get_value_global("game.version").unwrap(),text("1.10"));
+}
+
+#[test]
+fn local_init_asks_for_the_project_keys() {
+    let sandbox = Sandbox::new("local");
+
+    let mut prompter = Recording::new(Scripted::new(&["game", "11.4", "n", "", ""]));
+    init::run(&Options::new().local(true),&mut prompter).unwrap();
+
+    let questions = prompter.questions();
+    assert_eq!(questions.len(),5);
+    assert!(questions[0].contains("project.name"));
+    // switches are a yes or no question
+    assert!(questions[2].contains("lprun.repo.use_defaults"));
+    assert!(questions[2].ends_with("[Y/n]"));
+
+    assert!(sandbox.local_file().exists());
+    assert_eq!(lpsettings::get_value_local("project.name").unwrap(),text("game"));
+    assert_eq!(lpsettings::get_value_local("lprun.repo.use_defaults").unwrap(),Some(Type::Switch(false)));
+    assert_eq!(lpsettings::get_value_global("project.name").unwrap(),None);
+}
+
+#[test]
+fn local_init_needs_force_for_an_existing_file() {
+    let sandbox = Sandbox::new("force");
+
+    fs::write(sandbox.local_file(),"[project]\nname = \"game\"\n").unwrap();
+
+    assert!(init::run(&Options::new().local(true),&mut Scripted::new(&["other", "", "", "", ""])).is_err());
+    assert_eq!(lpsettings::get_value_local("project.name").unwrap(),text("game"));
+
+    init::run(&Options::new().local(true).force(true),&mut Scripted::new(&["other", "", "", "", ""])).unwrap();
+    assert_eq!(lpsettings::get_value_local("project.name").unwrap(),text("other"));
+}
+
+#[test]
+fn empty_switch_answers_are_left_unset() {
+    let _sandbox = Sandbox::new("switch");
+
+    init::run(&Options::new().local(true),&mut Scripted::new(&["tools", "", "", "", "", ""])).unwrap();
+
+    assert_eq!(lpsettings::get_value_local("project.name").unwrap(),text("tools"));
+    assert_eq!(lpsettings::get_value_local("lprun.repo.use_defaults").unwrap(),None);
+}
+
+#[test]
+fn local_init_in_a_subfolder_starts_a_new_project() {
+    let sandbox = Sandbox::new("nested");
+
+    fs::write(sandbox.local_file(),"[project]\nname = \"games\"\n").unwrap();
+    let tools = sandbox.path.join("project").join("tools");
+    fs::create_dir_all(&tools).unwrap();
+    env::set_current_dir(&tools).unwrap();
+
+    // nothing answered, nothing is made
+    init::run(&Options::new().local(true),&mut Scripted::new(&["", "", "", "", "", ""])).unwrap();
+    assert_eq!(lpsettings::project_root(),Some(sandbox.path.join("project")));
+
+    init::run(&Options::new().local(true),&mut Scripted::new(&["tools", "", "", "", "", ""])).unwrap();
+    assert_eq!(lpsettings::project_root(),Some(tools.clone()));
+    assert_eq!(lpsettings::get_value_local("project.name").unwrap(),text("tools"));
+    assert!(fs::read_to_string(sandbox.local_file()).unwrap().contains("games"));
+
+    // the file above doesn't need --force, the one here does
+    assert!(init::run(&Options::new().local(true).set("project.name","again"),&mut Scripted::new::<&str>(&[])).is_err());
+}
+
+#[test]
+fn invalid_answers_are_asked_again() {
+    let _sandbox = Sandbox::new("again");
+
+    let mut prompter = Recording::new(Scripted::new(&["game", "", "maybe", "yes", "", ""]));
+    init::run(&Options::new().local(true),&mut prompter).unwrap();
+
+    // the switch was asked twice
+    let questions = prompter.questions();
+    assert_eq!(questions.len(),6);
+    assert_eq!(questions[2],questions[3]);
+    assert!(prompter.said().iter().any(|message| message.contains("'maybe'")));
+
+    assert_eq!(lpsettings::get_value_local("lprun.repo.use_defaults").unwrap(),Some(Type::Switch(true)));
+}
+
+#[test]
+fn changes_are_reviewed_before_saving() {
+    let _sandbox = Sandbox::new("review");
+
+    init::run(&Options::new(),&mut Scripted::new(&["ada", "", ""])).unwrap();
+
+    let mut prompter = Recording::new(Scripted::new(&["bob", "", "n"]));
+    let written = init::run(&Options::new(),&mut prompter).unwrap();
+
+    // the old and new values are shown, then nothing is saved
+    assert!(prompter.said().iter().any(|message| message.contains("ada") && message.contains("bob")));
+    assert!(written.is_empty());
+    assert_eq!(lpsettings::get_value_global("user.name").unwrap(),text("ada"));
+}
+
+#[test]
+fn nothing_to_review_when_nothing_changes() {
+    let _sandbox = Sandbox::new("no-changes");
+
+    let mut prompter = Recording::new(Scripted::new(&["", ""]));
+    let written = init::run(&Options::new(),&mut prompter).unwrap();
+
+    assert_eq!(prompter.questions().len(),2);
+    assert!(written.is_empty());
+}
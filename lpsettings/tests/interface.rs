@@ -0,0 +1,303 @@
+//! runs the cli subcommand, capturing what it writes.
+
+#![cfg(feature = "cli")]
+
+extern crate lpsettings;
+
+mod common;
+
+use std::env;
+use std::fs;
+
+use lpsettings::{ Type, Scope };
+use lpsettings::scope;
+use lpsettings::interface::{ self, Outcome };
+use lpsettings::theme::{ self, ColorChoice };
+
+use common::Sandbox;
+
+fn run(args : &[&str]) -> (Outcome,String) {
+    theme::set_color(ColorChoice::Never);
+
+    let mut command = vec!["lpsettings"];
+    command.extend_from_slice(args);
+    let matches = interface::app().get_matches_from(command);
+
+    let mut out : Vec<u8> = Vec::new();
+    let outcome = interface::process_to(&matches,&mut out).unwrap();
+    (outcome,String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn set_and_get_a_value() {
+    let _sandbox = Sandbox::new("interface-set");
+
+    let (outcome, output) = run(&["user.name", "ada"]);
+    assert_eq!(outcome.command,"set");
+    assert_eq!(outcome.changed,vec!["user.name"]);
+    assert_eq!(output,"Set user.name to ada \n");
+
+    let (outcome, output) = run(&["user.name"]);
+    assert_eq!(outcome.command,"get");
+    assert_eq!(outcome.value,Some(Type::Text("ada".to_string())));
+    assert_eq!(output,"user.name: ada \n");
+}
+
+#[test]
+fn list_in_a_format() {
+    let _sandbox = Sandbox::new("interface-list");
+
+    run(&["user.name", "ada"]);
+    run(&["user.email", "ada@example.com"]);
+
+    let (outcome, output) = run(&["list", "--format", "raw"]);
+    assert_eq!(outcome.command,"list");
+    assert_eq!(output,"user.email=ada@example.com\nuser.name=ada\n");
+}
+
+#[test]
+fn scope_flags_only_apply_to_that_run() {
+    let _sandbox = Sandbox::new("interface-scope");
+
+    run(&["user.name", "ada"]);
+    let (_, output) = run(&["--local", "user.name", "bob"]);
+    assert_eq!(output,"Locally set user.name to bob \n");
+
+    // nothing is left behind for the next run or the rest of the process
+    assert!(env::var(scope::ENV_VAR).is_err());
+    assert_eq!(lpsettings::get_value_in("user.name",Scope::Global).unwrap(),Some(Type::Text("ada".to_string())));
+    assert_eq!(lpsettings::get_value_in("user.name",Scope::Local).unwrap(),Some(Type::Text("bob".to_string())));
+
+    let (_, output) = run(&["--global", "list", "--format", "raw"]);
+    assert_eq!(output,"user.name=ada\n");
+}
+
+#[test]
+fn system_settings_are_below_the_global_ones() {
+    let sandbox = Sandbox::new("interface-system");
+
+    let (_, output) = run(&["--system", "user.name", "lab"]);
+    assert_eq!(output,"System-wide set user.name to lab \n");
+    assert!(sandbox.system_file().exists());
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),Some(Type::Text("lab".to_string())));
+
+    run(&["user.name", "ada"]);
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),Some(Type::Text("ada".to_string())));
+    assert_eq!(lpsettings::get_value_in("user.name",Scope::System).unwrap(),Some(Type::Text("lab".to_string())));
+
+    let (_, output) = run(&["list", "--effective", "user.name"]);
+    assert!(output.contains("[global] overrides system"));
+}
+
+#[test]
+fn system_settings_need_the_flag_to_change() {
+    let _sandbox = Sandbox::new("interface-system-env");
+
+    env::set_var(scope::ENV_VAR,"system");
+    let matches = interface::app().get_matches_from(vec!["lpsettings", "user.name", "lab"]);
+    let result = interface::process_to(&matches,&mut Vec::new());
+    env::remove_var(scope::ENV_VAR);
+
+    assert!(result.is_err());
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),None);
+}
+
+#[test]
+fn system_settings_cannot_be_encrypted() {
+    let sandbox = Sandbox::new("interface-system-encrypt");
+    env::set_var("LOVEPACK_KEY_FILE",sandbox.path.join("lovepack.key"));
+
+    let matches = interface::app().get_matches_from(vec!["lpsettings", "--system", "--encrypt", "user.email", "lab@example.com"]);
+    let result = interface::process_to(&matches,&mut Vec::new());
+    env::remove_var("LOVEPACK_KEY_FILE");
+
+    assert!(result.is_err());
+    assert!(!sandbox.system_file().exists());
+    assert_eq!(lpsettings::get_value_in("user.email",Scope::Global).unwrap(),None);
+}
+
+#[test]
+fn paths_shows_where_the_settings_are() {
+    let sandbox = Sandbox::new("interface-paths");
+
+    let (outcome, output) = run(&["paths"]);
+    assert_eq!(outcome.command,"paths");
+    assert!(output.contains("local: none found\n"));
+    assert!(output.contains(&format!("system: {} (missing, can be created)",sandbox.system_file().display())));
+
+    run(&["--local", "user.name", "ada"]);
+    let (_, output) = run(&["info", "--format", "raw"]);
+    assert!(output.contains(&format!("local={}\n",sandbox.local_file().display())));
+}
+
+#[test]
+fn paths_lists_files_that_cant_be_read() {
+    let sandbox = Sandbox::new("interface-paths-corrupt");
+
+    let global = sandbox.path.join("home").join(".lovepack").join("lovepack.toml");
+    fs::create_dir_all(global.parent().unwrap()).unwrap();
+    fs::write(&global,"= broken\n").unwrap();
+
+    let (outcome, output) = run(&["paths"]);
+    assert_eq!(outcome.command,"paths");
+    assert!(output.contains(&format!("global: {} (unreadable: ",global.display())));
+    assert!(output.contains("system: "));
+    assert!(output.contains("backups: "));
+}
+
+#[cfg(unix)]
+#[test]
+fn readable_secrets_are_warned_about_in_the_output() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let sandbox = Sandbox::new("interface-secrets-warning");
+
+    run(&["lprun.repo.token", "hunter2"]);
+    let secrets = sandbox.path.join("home").join(".lovepack").join("lovepack.secrets.toml");
+    fs::set_permissions(&secrets,fs::Permissions::from_mode(0o644)).unwrap();
+
+    let (_, output) = run(&["lprun.repo.token"]);
+    assert!(output.ends_with(&format!("Warning: {} can be read by other users, it should only be readable by you (chmod 600 {})\n",secrets.display(),secrets.display())));
+
+    // only once
+    let (_, output) = run(&["lprun.repo.token"]);
+    assert!(!output.contains("Warning"));
+}
+
+#[test]
+fn unset_a_value() {
+    let _sandbox = Sandbox::new("interface-unset");
+
+    run(&["user.name", "ada"]);
+
+    let (outcome, output) = run(&["unset", "user.name"]);
+    assert_eq!(outcome.changed,vec!["user.name"]);
+    assert_eq!(output,"Removed user.name, was ada\n");
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),None);
+}
+
+#[test]
+fn values_are_read_as_the_keys_type() {
+    let _sandbox = Sandbox::new("interface-typed");
+
+    run(&["lprun.repo.update.freq", "3"]);
+    assert_eq!(lpsettings::get_value("lprun.repo.update.freq").unwrap(),Some(Type::Int(3)));
+
+    let matches = interface::app().get_matches_from(vec!["lpsettings", "lprun.repo.update.freq", "often"]);
+    assert!(interface::process_to(&matches,&mut Vec::new()).is_err());
+    assert_eq!(lpsettings::get_value("lprun.repo.update.freq").unwrap(),Some(Type::Int(3)));
+
+    // keys without a type are kept as text
+    run(&["game.version", "1.10"]);
+    assert_eq!(lpsettings::get_value("game.version").unwrap(),Some(Type::Text("1.10".to_string())));
+}
+
+#[test]
+fn edit_only_checks_the_values_that_changed() {
+    let sandbox = Sandbox::new("interface-edit");
+
+    fs::write(sandbox.local_file(),"[lprun.repo.update]\nfreq = \"3\"\n\n[user]\nname = \"ada\"\n").unwrap();
+    env::set_var("VISUAL","");
+
+    env::set_var("EDITOR","true");
+    let (outcome, output) = run(&["--local", "edit"]);
+    assert!(outcome.changed.is_empty());
+    assert_eq!(output,"Locally no changes\n");
+
+    env::set_var("EDITOR","sed -i s/ada/bob/");
+    let (outcome, _) = run(&["--local", "edit"]);
+    assert_eq!(outcome.changed,vec!["user.name"]);
+    // the file is replaced by renaming the new one over it
+    assert!(fs::read_to_string(sandbox.local_file()).unwrap().contains("bob"));
+    assert!(!fs::read_dir(sandbox.local_file().parent().unwrap()).unwrap()
+        .any(|entry| entry.unwrap().file_name().to_string_lossy().starts_with(".lpsettings-save")));
+
+    env::set_var("EDITOR","sed -i s/\"3\"/\"4\"/");
+    let matches = interface::app().get_matches_from(vec!["lpsettings", "--local", "edit"]);
+    assert!(interface::process_to(&matches,&mut Vec::new()).is_err());
+    assert!(fs::read_to_string(sandbox.local_file()).unwrap().contains("\"3\""));
+
+    env::remove_var("EDITOR");
+    env::remove_var("VISUAL");
+}
+
+#[test]
+fn referenced_secrets_are_masked() {
+    let _sandbox = Sandbox::new("interface-references");
+
+    run(&["lprun.repo.token", "hunter2"]);
+    run(&["lprun.repo.links", "https://${lprun.repo.token}@example.com"]);
+    run(&["--local", "lprun.repo.links", "${user.name}"]);
+
+    let (_, output) = run(&["--global", "lprun.repo.links"]);
+    assert_eq!(output,"lprun.repo.links: https://****@example.com globally\n");
+
+    let (_, output) = run(&["--global", "--reveal", "lprun.repo.links"]);
+    assert!(output.contains("https://hunter2@example.com"));
+
+    // the raw value is read from the scope that was asked for
+    let (outcome, _) = run(&["--global", "--no-expand", "lprun.repo.links"]);
+    assert_eq!(outcome.value,Some(Type::Text("https://${lprun.repo.token}@example.com".to_string())));
+}
+
+#[test]
+fn the_profile_flag_only_applies_to_that_run() {
+    let _sandbox = Sandbox::new("interface-profile");
+
+    run(&["user.name", "ada"]);
+    run(&["profile", "create", "ci"]);
+    run(&["--profile", "ci", "user.name", "ci-bot"]);
+
+    let (outcome, _) = run(&["--profile", "ci", "user.name"]);
+    assert_eq!(outcome.value,Some(Type::Text("ci-bot".to_string())));
+    let (outcome, _) = run(&["user.name"]);
+    assert_eq!(outcome.value,Some(Type::Text("ada".to_string())));
+}
+
+#[test]
+fn list_agrees_with_get() {
+    let sandbox = Sandbox::new("interface-list-get");
+    env::set_var("LOVEPACK_KEY_FILE",sandbox.path.join("lovepack.key"));
+
+    run(&["--encrypt", "user.email", "ada@example.com"]);
+    run(&["user.name", "ada"]);
+    run(&["project.build", "${user.name}-build"]);
+    run(&["profile", "create", "ci"]);
+    run(&["--profile", "ci", "user.name", "ci-bot"]);
+
+    // encrypted values are hidden in every format
+    let (_, output) = run(&["list", "--format", "json"]);
+    assert!(output.contains("\"[encrypted]\""));
+    assert!(!output.contains("enc:v1:"));
+    assert!(output.contains("\"ada-build\""));
+
+    let (_, output) = run(&["--profile", "ci", "list", "--format", "raw"]);
+    assert!(output.contains("project.build=ci-bot-build\n"));
+    assert!(output.ends_with("user.name=ci-bot\n"));
+
+    env::remove_var("LOVEPACK_KEY_FILE");
+}
+
+#[test]
+fn the_color_flag_is_used_over_the_environment() {
+    let _sandbox = Sandbox::new("interface-color");
+
+    env::set_var("NO_COLOR","1");
+    let (_, output) = run(&["--color", "always", "user.name", "ada"]);
+    env::remove_var("NO_COLOR");
+    assert!(output.contains("\u{1b}["));
+
+    env::set_var("CLICOLOR_FORCE","1");
+    let (_, output) = run(&["--color", "never", "user.name"]);
+    env::remove_var("CLICOLOR_FORCE");
+    assert_eq!(output,"user.name: ada \n");
+}
+
+#[test]
+fn nothing_to_do() {
+    let _sandbox = Sandbox::new("interface-nothing");
+
+    let (outcome, output) = run(&[]);
+    assert_eq!(outcome,Outcome::default());
+    assert!(output.is_empty());
+}
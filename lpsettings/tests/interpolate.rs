@@ -0,0 +1,70 @@
+//! expanding references to other settings and the environment.
+
+extern crate lpsettings;
+
+mod common;
+
+use std::env;
+
+use lpsettings::{ Type, Scope };
+use lpsettings::error::{ self, code };
+
+use common::Sandbox;
+
+fn text(value : &str) -> Option<Type> {
+    Some(Type::Text(value.to_string()))
+}
+
+#[test]
+fn references_are_expanded() {
+    let _sandbox = Sandbox::new("interpolate-expand");
+
+    env::set_var("LPTEST_BUILD_DIR","/tmp/build");
+    lpsettings::set_value("user.name",&"ada").unwrap();
+    lpsettings::set_value("project.build",&"${user.name}-build").unwrap();
+    lpsettings::set_value("project.output",&"${env:LPTEST_BUILD_DIR}/${project.build}").unwrap();
+
+    assert_eq!(lpsettings::get_value("project.build").unwrap(),text("ada-build"));
+    assert_eq!(lpsettings::get_value("project.output").unwrap(),text("/tmp/build/ada-build"));
+    assert_eq!(lpsettings::get_value_raw("project.build").unwrap(),text("${user.name}-build"));
+    assert_eq!(lpsettings::get_value_raw_in("project.build",Scope::Local).unwrap(),None);
+
+    env::remove_var("LPTEST_BUILD_DIR");
+}
+
+#[test]
+fn escapes_and_lone_references_are_kept() {
+    let _sandbox = Sandbox::new("interpolate-escape");
+
+    lpsettings::set_value("user.name",&"costs $${PRICE} and $5").unwrap();
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),text("costs ${PRICE} and $5"));
+
+    lpsettings::set_value("user.name",&"half ${open").unwrap();
+    assert_eq!(lpsettings::get_value("user.name").unwrap(),text("half ${open"));
+}
+
+#[test]
+fn missing_references_are_errors() {
+    let _sandbox = Sandbox::new("interpolate-missing");
+
+    lpsettings::set_value("project.build",&"${user.nobody}-build").unwrap();
+    let error = lpsettings::get_value("project.build").unwrap_err();
+    assert_eq!(error::exit_code(&error),code::KEY_NOT_FOUND);
+
+    lpsettings::set_value("project.build",&"${env:LPTEST_UNSET}").unwrap();
+    let error = lpsettings::get_value("project.build").unwrap_err();
+    assert_eq!(error::exit_code(&error),code::KEY_NOT_FOUND);
+}
+
+#[test]
+fn cycles_are_errors() {
+    let _sandbox = Sandbox::new("interpolate-cycle");
+
+    lpsettings::set_value("user.name",&"${user.email}").unwrap();
+    lpsettings::set_value("user.email",&"${user.name}").unwrap();
+    let error = lpsettings::get_value("user.name").unwrap_err();
+    assert_eq!(error::exit_code(&error),code::INVALID_INPUT);
+
+    lpsettings::set_value("user.name",&"${user.name}").unwrap();
+    assert!(lpsettings::get_value("user.name").is_err());
+}
@@ -0,0 +1,39 @@
+//! the patterns `list` filters the keys with.
+
+extern crate lpsettings;
+
+use lpsettings::list::matches;
+
+#[test]
+fn prefixes_match_the_keys_below_them() {
+    assert!(matches("lprun","lprun.repo.links"));
+    assert!(matches("lprun.repo","lprun.repo.links"));
+    assert!(matches("lprun.repo.links","lprun.repo.links"));
+    // only whole segments
+    assert!(!matches("lprun.re","lprun.repo.links"));
+    assert!(!matches("lprun.repo.links.extra","lprun.repo.links"));
+    assert!(!matches("user","lprun.repo.links"));
+}
+
+#[test]
+fn a_star_stays_in_one_segment() {
+    assert!(matches("lprun.*.freq","lprun.update.freq"));
+    assert!(matches("*.name","user.name"));
+    assert!(matches("user.*","user.name"));
+    assert!(matches("user.na*","user.name"));
+    assert!(matches("user.*a*e","user.name"));
+    assert!(matches("user.nam?","user.name"));
+    assert!(!matches("lprun.*.freq","lprun.repo.update.freq"));
+    assert!(!matches("user.nam?","user.nam"));
+    assert!(matches("*","lprun"));
+}
+
+#[test]
+fn a_double_star_crosses_segments() {
+    assert!(matches("**.freq","lprun.repo.update.freq"));
+    assert!(matches("lprun.**.freq","lprun.update.freq"));
+    assert!(matches("lprun.**.freq","lprun.repo.update.freq"));
+    assert!(matches("**","user.name"));
+    assert!(!matches("**.freq","lprun.repo.update.last"));
+    assert!(!matches("user.**.freq","lprun.repo.update.freq"));
+}
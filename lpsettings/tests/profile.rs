@@ -0,0 +1,96 @@
+//! profiles overlaying the base settings.
+
+extern crate lpsettings;
+
+mod common;
+
+use std::env;
+use std::fs;
+
+use lpsettings::{ Type, Scope };
+use lpsettings::profile;
+use lpsettings::list::{ self, Options };
+use lpsettings::output::OutputFormat;
+
+use common::Sandbox;
+
+const SETTINGS : &str = r#"
+[lprun.repo]
+links = "https://example.com/links.toml"
+
+[lprun.repo.update]
+freq = 7
+last = "2024-01-01"
+
+[profiles.ci.lprun.repo.update]
+freq = 0
+"#;
+
+fn text(value : &str) -> Option<Type> {
+    Some(Type::Text(value.to_string()))
+}
+
+#[test]
+fn a_profile_is_only_used_when_asked_for() {
+    let sandbox = Sandbox::new("profile-explicit");
+    fs::write(sandbox.local_file(),SETTINGS).unwrap();
+
+    assert_eq!(profile::get_value("ci","lprun.repo.update.freq").unwrap(),Some(Type::Int(0)));
+    // nothing is left selected afterwards
+    assert_eq!(lpsettings::get_value("lprun.repo.update.freq").unwrap(),Some(Type::Int(7)));
+    assert_eq!(profile::get_active().unwrap(),None);
+
+    env::set_var("LOVEPACK_PROFILE","ci");
+    assert_eq!(lpsettings::get_value("lprun.repo.update.freq").unwrap(),Some(Type::Int(0)));
+    env::remove_var("LOVEPACK_PROFILE");
+}
+
+#[test]
+fn nested_tables_are_merged() {
+    let sandbox = Sandbox::new("profile-nested");
+    fs::write(sandbox.local_file(),SETTINGS).unwrap();
+
+    // the profile only has `update.freq`, everything else is kept
+    let repo = match profile::get_value("ci","lprun.repo").unwrap() {
+        Some(Type::Complex(repo)) => repo,
+        value => panic!("expected a table, got {:?}",value),
+    };
+    assert_eq!(repo.get("links").cloned(),text("https://example.com/links.toml"));
+
+    let update = match repo.get("update") {
+        Some(Type::Complex(update)) => update,
+        value => panic!("expected a table, got {:?}",value),
+    };
+    assert_eq!(update.get("freq"),Some(&Type::Int(0)));
+    assert_eq!(update.get("last").cloned(),text("2024-01-01"));
+}
+
+#[test]
+fn the_profiles_are_listed_on_their_own() {
+    let sandbox = Sandbox::new("profile-list");
+    fs::write(sandbox.local_file(),SETTINGS).unwrap();
+    lpsettings::set_value("profiles.ci.user.name",&"ci-bot").unwrap();
+
+    let mut out : Vec<u8> = Vec::new();
+    list::current(&mut out,&Options::new(OutputFormat::Raw).profile(Some("ci"))).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(!out.contains("profiles."));
+    assert!(out.contains("user.name=ci-bot\n"));
+    assert!(out.contains("lprun.repo.update.freq=0\n"));
+}
+
+#[test]
+fn a_profile_is_deleted_where_it_is() {
+    let sandbox = Sandbox::new("profile-delete");
+    fs::write(sandbox.local_file(),SETTINGS).unwrap();
+    lpsettings::set_value("profiles.ci.user.name",&"ci-bot").unwrap();
+    profile::use_profile(Some("ci")).unwrap();
+
+    profile::delete("ci").unwrap();
+    assert!(profile::list().unwrap().is_empty());
+    assert_eq!(lpsettings::get_value_in("profiles.ci",Scope::Local).unwrap(),None);
+    assert_eq!(lpsettings::get_value_in("profiles.ci",Scope::Global).unwrap(),None);
+    assert_eq!(profile::get_active().unwrap(),None);
+
+    assert!(profile::delete("ci").is_err());
+}
@@ -0,0 +1,109 @@
+//! finding the project's local settings from folders inside it.
+
+extern crate lpsettings;
+
+mod common;
+
+use std::env;
+use std::fs;
+
+use lpsettings::Type;
+use lpsettings::{ diff, list, project };
+use lpsettings::output::OutputFormat;
+
+use common::Sandbox;
+
+#[test]
+fn local_settings_are_found_from_a_subfolder() {
+    let sandbox = Sandbox::new("project-subfolder");
+
+    fs::write(sandbox.local_file(),"[project]\nname = \"game\"\n").unwrap();
+    let src = sandbox.path.join("project").join("src").join("states");
+    fs::create_dir_all(&src).unwrap();
+    env::set_current_dir(&src).unwrap();
+
+    assert_eq!(lpsettings::project_root(),Some(sandbox.path.join("project")));
+    assert_eq!(lpsettings::get_value_local("project.name").unwrap(),Some(Type::Text("game".to_string())));
+
+    // and written back to the same file
+    lpsettings::set_value_local("project.love_version",&"11.4").unwrap();
+    assert!(!src.join("lovepack.toml").exists());
+    assert!(fs::read_to_string(sandbox.local_file()).unwrap().contains("11.4"));
+}
+
+#[test]
+fn the_search_stops_at_a_repository_root() {
+    let sandbox = Sandbox::new("project-vcs");
+
+    fs::write(sandbox.local_file(),"[project]\nname = \"outer\"\n").unwrap();
+    let repo = sandbox.path.join("project").join("vendor").join("lib");
+    fs::create_dir_all(repo.join(".git")).unwrap();
+    fs::create_dir_all(repo.join("src")).unwrap();
+
+    assert_eq!(project::find_root_from(repo.join("src")),None);
+    assert_eq!(project::find_root_from(sandbox.path.join("project").join("vendor")),Some(sandbox.path.join("project")));
+
+    // without a project the current directory is used
+    env::set_current_dir(repo.join("src")).unwrap();
+    assert_eq!(lpsettings::project_root(),None);
+    assert_eq!(lpsettings::get_value_local("project.name").unwrap(),None);
+}
+
+#[test]
+fn nested_projects_inherit_the_settings_above_them() {
+    let sandbox = Sandbox::new("project-nested");
+
+    let outer = sandbox.path.join("project");
+    let inner = outer.join("games").join("platformer");
+    fs::create_dir_all(inner.join("src")).unwrap();
+    fs::write(outer.join("lovepack.toml"),"[project]\nname = \"games\"\nlove_version = \"11.4\"\n").unwrap();
+    fs::write(inner.join("lovepack.toml"),"[project]\nname = \"platformer\"\n").unwrap();
+    env::set_current_dir(inner.join("src")).unwrap();
+
+    assert_eq!(project::files().unwrap(),vec![inner.join("lovepack.toml"),outer.join("lovepack.toml")]);
+
+    // the closest file wins
+    assert_eq!(lpsettings::get_value("project.name").unwrap(),Some(Type::Text("platformer".to_string())));
+    assert_eq!(lpsettings::get_value_local("project.love_version").unwrap(),Some(Type::Text("11.4".to_string())));
+
+    // only the project's own file is written to
+    lpsettings::set_value_local("project.love_version",&"0.10.2").unwrap();
+    assert_eq!(lpsettings::get_value("project.love_version").unwrap(),Some(Type::Text("0.10.2".to_string())));
+    assert!(fs::read_to_string(outer.join("lovepack.toml")).unwrap().contains("11.4"));
+}
+
+#[test]
+fn a_root_project_does_not_inherit() {
+    let sandbox = Sandbox::new("project-root");
+
+    let outer = sandbox.path.join("project");
+    let inner = outer.join("tools");
+    fs::create_dir_all(&inner).unwrap();
+    fs::write(outer.join("lovepack.toml"),"[project]\nlove_version = \"11.4\"\n").unwrap();
+    fs::write(inner.join("lovepack.toml"),"root = true\n\n[project]\nname = \"tools\"\n").unwrap();
+    env::set_current_dir(&inner).unwrap();
+
+    assert_eq!(project::files().unwrap(),vec![inner.join("lovepack.toml")]);
+    assert_eq!(lpsettings::get_value("project.love_version").unwrap(),None);
+}
+
+#[test]
+fn inherited_settings_are_listed_and_compared() {
+    let sandbox = Sandbox::new("project-inherited-list");
+
+    let outer = sandbox.path.join("project");
+    let inner = outer.join("games").join("platformer");
+    fs::create_dir_all(&inner).unwrap();
+    fs::write(outer.join("lovepack.toml"),"[project]\nlove_version = \"11.4\"\n").unwrap();
+    fs::write(inner.join("lovepack.toml"),"[project]\nname = \"platformer\"\n").unwrap();
+    env::set_current_dir(&inner).unwrap();
+
+    let mut out : Vec<u8> = Vec::new();
+    list::current(&mut out,&list::Options::new(OutputFormat::Raw)).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("project.love_version=11.4\n"));
+    assert!(out.contains("project.name=platformer\n"));
+
+    let changes = diff::scopes().unwrap();
+    assert!(changes.iter().any(|change| change.key == "project.love_version"));
+}
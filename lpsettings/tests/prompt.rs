@@ -0,0 +1,60 @@
+//! the questions built on `Prompter::ask`.
+
+extern crate lpsettings;
+
+use lpsettings::prompt::{ Prompter, Scripted, Recording };
+
+fn choices() -> Vec<String> {
+    vec![ "weekly".to_string(), "daily".to_string(), "never".to_string() ]
+}
+
+#[test]
+fn confirm_understands_yes_and_no() {
+    let mut prompter = Scripted::new(&["y", "No", "", ""]);
+
+    assert!(prompter.confirm("sure?",false).unwrap());
+    assert!(!prompter.confirm("sure?",true).unwrap());
+    assert!(prompter.confirm("sure?",true).unwrap());
+    assert!(!prompter.confirm("sure?",false).unwrap());
+}
+
+#[test]
+fn confirm_asks_again_until_it_understands() {
+    let mut prompter = Recording::new(Scripted::new(&["perhaps", "yes"]));
+
+    assert!(prompter.confirm("sure?",false).unwrap());
+    assert_eq!(prompter.questions(),vec!["sure? [y/N]", "sure? [y/N]"]);
+    assert_eq!(prompter.said().len(),1);
+}
+
+#[test]
+fn choose_shows_a_numbered_menu() {
+    let mut prompter = Recording::new(Scripted::new(&["2"]));
+
+    assert_eq!(prompter.choose("how often?",&choices()).unwrap(),Some(1));
+    assert_eq!(prompter.said(),&[ "  1) weekly", "  2) daily", "  3) never" ]);
+    assert_eq!(prompter.questions(),vec!["how often? [1-3]"]);
+}
+
+#[test]
+fn choose_takes_the_choice_or_nothing() {
+    let mut prompter = Scripted::new(&["never", ""]);
+
+    assert_eq!(prompter.choose("how often?",&choices()).unwrap(),Some(2));
+    assert_eq!(prompter.choose("how often?",&choices()).unwrap(),None);
+}
+
+#[test]
+fn choose_asks_again_when_out_of_range() {
+    let mut prompter = Scripted::new(&["4", "0", "hourly", "1"]);
+
+    assert_eq!(prompter.choose("how often?",&choices()).unwrap(),Some(0));
+    assert_eq!(prompter.remaining(),0);
+}
+
+#[test]
+fn scripts_can_run_out() {
+    let mut prompter = Scripted::new(&["maybe"]);
+
+    assert!(prompter.confirm("sure?",true).is_err());
+}
@@ -0,0 +1,63 @@
+//! secret values are kept in their own files, readable only by the user.
+
+extern crate lpsettings;
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use lpsettings::Type;
+use lpsettings::secrets;
+
+use common::Sandbox;
+
+#[cfg(unix)]
+fn assert_restricted(path : &Path) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777,0o600,"{} is {:o}",path.display(),mode & 0o777);
+}
+
+#[cfg(not(unix))]
+fn assert_restricted(path : &Path) {
+    assert!(path.exists());
+}
+
+#[test]
+fn secrets_are_kept_apart() {
+    let sandbox = Sandbox::new("secrets-global");
+
+    assert!(secrets::is_secret("lprun.repo.token"));
+    assert!(!secrets::is_secret("lprun.repo.links"));
+
+    lpsettings::set_value("lprun.repo.token",&"hunter2").unwrap();
+    lpsettings::set_value("user.name",&"ada").unwrap();
+
+    let folder = sandbox.path.join("home").join(".lovepack");
+    assert_restricted(&folder.join("lovepack.secrets.toml"));
+    assert!(!fs::read_to_string(folder.join("lovepack.toml")).unwrap().contains("hunter2"));
+    assert_eq!(lpsettings::get_value("lprun.repo.token").unwrap(),Some(Type::Text("hunter2".to_string())));
+
+    // changing the global secrets doesn't make a local file
+    assert!(!sandbox.path.join("project").join("lovepack.secrets.toml").exists());
+}
+
+#[test]
+fn local_secrets_are_restricted() {
+    let sandbox = Sandbox::new("secrets-local");
+
+    let path = sandbox.path.join("import.toml");
+    fs::write(&path,"[lprun.repo]\ntoken = \"hunter2\"\n").unwrap();
+    lpsettings::import_local(&path).unwrap();
+
+    let local = sandbox.path.join("project").join("lovepack.secrets.toml");
+    assert_restricted(&local);
+    assert!(fs::read_to_string(&local).unwrap().contains("hunter2"));
+
+    // a global change keeps the local file as it was
+    lpsettings::set_value("lprun.repo.token",&"swordfish").unwrap();
+    assert_restricted(&local);
+    assert_eq!(lpsettings::get_value("lprun.repo.token").unwrap(),Some(Type::Text("hunter2".to_string())));
+}
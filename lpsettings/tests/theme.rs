@@ -0,0 +1,98 @@
+//! when colors are used, and the styles of the theme.
+
+extern crate lpsettings;
+extern crate ansi_term;
+
+mod common;
+
+use std::env;
+use std::fs;
+
+use ansi_term::{ Colour, Style };
+
+use lpsettings::theme::{ self, ColorChoice, Role, Theme };
+
+use common::Sandbox;
+
+fn colors(flag : ColorChoice) -> bool {
+    //! if colors are used with `--color` set to `flag`. the tests
+    //! don't run in a terminal, so `auto` is off.
+
+    theme::set_color(flag);
+    theme::colors_enabled()
+}
+
+#[test]
+fn color_choices_take_precedence_in_order() {
+    let _sandbox = Sandbox::new("theme-colors");
+    env::remove_var("NO_COLOR");
+    env::remove_var("CLICOLOR_FORCE");
+
+    // not a terminal
+    assert!(!colors(ColorChoice::Auto));
+
+    // the setting
+    lpsettings::set_value("lpsettings.color",&"always").unwrap();
+    assert!(colors(ColorChoice::Auto));
+    lpsettings::set_value("lpsettings.color",&"never").unwrap();
+    assert!(!colors(ColorChoice::Auto));
+
+    // then CLICOLOR_FORCE
+    env::set_var("CLICOLOR_FORCE","1");
+    assert!(colors(ColorChoice::Auto));
+    env::set_var("CLICOLOR_FORCE","0");
+    assert!(!colors(ColorChoice::Auto));
+    env::set_var("CLICOLOR_FORCE","1");
+
+    // then NO_COLOR
+    lpsettings::set_value("lpsettings.color",&"always").unwrap();
+    env::set_var("NO_COLOR","1");
+    assert!(!colors(ColorChoice::Auto));
+
+    // and --color over everything
+    assert!(colors(ColorChoice::Always));
+    env::remove_var("NO_COLOR");
+    assert!(!colors(ColorChoice::Never));
+
+    env::remove_var("CLICOLOR_FORCE");
+    theme::set_color(ColorChoice::Never);
+}
+
+#[test]
+fn styles_are_read_from_text() {
+    assert_eq!(theme::parse_style("yellow").unwrap(),Colour::Yellow.normal());
+    assert_eq!(theme::parse_style("Bold Red").unwrap(),Colour::Red.bold());
+    assert_eq!(theme::parse_style("underline 214").unwrap(),Colour::Fixed(214).underline());
+    assert_eq!(theme::parse_style("#ff8800").unwrap(),Colour::RGB(255,136,0).normal());
+    assert_eq!(theme::parse_style("black on yellow").unwrap(),Colour::Black.on(Colour::Yellow));
+    assert_eq!(theme::parse_style("dim italic reverse").unwrap(),Style::new().dimmed().italic().reverse());
+    assert_eq!(theme::parse_style("plain").unwrap(),Style::new());
+
+    assert!(theme::parse_style("sparkly").is_err());
+    assert!(theme::parse_style("#ff88").is_err());
+    assert!(theme::parse_style("300").is_err());
+}
+
+#[test]
+fn presets_can_be_changed_by_the_settings() {
+    let sandbox = Sandbox::new("theme-override");
+
+    assert_eq!(Theme::preset("default").unwrap(),Theme::default());
+    assert!(Theme::preset("neon").is_err());
+
+    lpsettings::set_value("lpsettings.theme.preset",&"mono").unwrap();
+    lpsettings::set_value("lpsettings.theme.key",&"bold red").unwrap();
+
+    let mono = Theme::preset("mono").unwrap();
+    let theme = theme::load_theme();
+    assert_eq!(theme.style(Role::Key),Colour::Red.bold());
+    assert_eq!(theme.style(Role::Value),mono.style(Role::Value));
+
+    // the theme file is below the settings
+    let folder = sandbox.path.join("home").join(".lovepack");
+    fs::write(folder.join("theme.toml"),"preset = \"light\"\nkey = \"green\"\nvalue = \"#ff8800\"\n").unwrap();
+    let theme = theme::load_theme();
+    assert_eq!(theme.style(Role::Key),Colour::Red.bold());
+    assert_eq!(theme.style(Role::Value),Colour::RGB(255,136,0).normal());
+    assert_eq!(theme.style(Role::Comment),mono.style(Role::Comment));
+}